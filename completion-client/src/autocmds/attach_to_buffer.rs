@@ -15,12 +15,24 @@ pub(crate) fn attach_to_buffer(
             Ok(false)
         });
 
+    let on_filetype =
+        client.to_nvim_fn(|client, args: AutocmdCallbackArgs| {
+            super::on_filetype(client, args.buffer)?;
+            Ok(false)
+        });
+
     let on_insert_leave =
         client.to_nvim_fn(|client, args: AutocmdCallbackArgs| {
             super::on_insert_leave(client, args.buffer)?;
             Ok(false)
         });
 
+    let on_text_changed_i =
+        client.to_nvim_fn(|client, args: AutocmdCallbackArgs| {
+            super::on_text_changed_i(client, args.buffer)?;
+            Ok(false)
+        });
+
     let on_vim_resized =
         client.to_nvim_fn(|client, _: AutocmdCallbackArgs| {
             super::on_vim_resized(client)?;
@@ -35,11 +47,21 @@ pub(crate) fn attach_to_buffer(
         &builder.clone().callback(on_cursor_moved_i).build(),
     )?;
 
+    api::create_autocmd(
+        ["FileType"],
+        &builder.clone().callback(on_filetype).build(),
+    )?;
+
     api::create_autocmd(
         ["InsertLeave"],
         &builder.clone().callback(on_insert_leave).build(),
     )?;
 
+    api::create_autocmd(
+        ["TextChangedI"],
+        &builder.clone().callback(on_text_changed_i).build(),
+    )?;
+
     api::create_autocmd(
         ["VimResized"],
         &builder.clone().callback(on_vim_resized).build(),
@@ -3,15 +3,25 @@
 mod attach_to_buffer;
 mod on_buf_enter;
 mod on_buf_new;
+mod on_cmdline_changed;
+mod on_cmdline_enter;
+mod on_cmdline_leave;
 mod on_cursor_moved_i;
+mod on_filetype;
 mod on_insert_leave;
+mod on_text_changed_i;
 mod on_vim_resized;
 mod setup;
 
 pub(crate) use attach_to_buffer::attach_to_buffer as attach;
 use on_buf_enter::on_buf_enter;
 use on_buf_new::on_buf_new;
+use on_cmdline_changed::on_cmdline_changed;
+use on_cmdline_enter::on_cmdline_enter;
+use on_cmdline_leave::on_cmdline_leave;
 use on_cursor_moved_i::on_cursor_moved_i;
+use on_filetype::on_filetype;
 use on_insert_leave::on_insert_leave;
+use on_text_changed_i::on_text_changed_i;
 use on_vim_resized::on_vim_resized;
 pub(crate) use setup::setup;
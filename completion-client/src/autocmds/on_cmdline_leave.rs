@@ -0,0 +1,5 @@
+use crate::{Client, Result};
+
+pub(super) fn on_cmdline_leave(client: &Client) -> Result<()> {
+    client.on_cmdline_leave()
+}
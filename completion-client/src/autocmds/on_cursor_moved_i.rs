@@ -1,13 +1,8 @@
 use nvim::api::Buffer;
 use nvim_oxi as nvim;
 
-use crate::Client;
+use crate::{Client, Result};
 
-pub(super) fn on_cursor_moved_i(
-    client: &Client,
-    buf: Buffer,
-) -> nvim::Result<()> {
-    // client.stop_sending();
-
-    Ok(())
+pub(super) fn on_cursor_moved_i(client: &Client, buf: Buffer) -> Result<()> {
+    client.on_cursor_moved(buf)
 }
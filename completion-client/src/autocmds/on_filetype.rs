@@ -0,0 +1,8 @@
+use nvim::api::Buffer;
+use nvim_oxi as nvim;
+
+use crate::{Client, Result};
+
+pub(super) fn on_filetype(client: &Client, buf: Buffer) -> Result<()> {
+    client.on_filetype_changed(buf)
+}
@@ -26,5 +26,42 @@ pub(crate) fn setup(client: &Client) -> Result<u32> {
 
     api::create_autocmd(["BufNew"], &opts)?;
 
+    // The command-line isn't tied to a buffer, so these are global.
+    let on_cmdline_enter =
+        client.to_nvim_fn(|client, _: AutocmdCallbackArgs| {
+            super::on_cmdline_enter(client)?;
+            Ok(false)
+        });
+
+    let on_cmdline_changed =
+        client.to_nvim_fn(|client, _: AutocmdCallbackArgs| {
+            super::on_cmdline_changed(client)?;
+            Ok(false)
+        });
+
+    let on_cmdline_leave =
+        client.to_nvim_fn(|client, _: AutocmdCallbackArgs| {
+            super::on_cmdline_leave(client)?;
+            Ok(false)
+        });
+
+    let mut builder = CreateAutocmdOpts::builder();
+    builder.group(augroup_id);
+
+    api::create_autocmd(
+        ["CmdlineEnter"],
+        &builder.clone().callback(on_cmdline_enter).build(),
+    )?;
+
+    api::create_autocmd(
+        ["CmdlineChanged"],
+        &builder.clone().callback(on_cmdline_changed).build(),
+    )?;
+
+    api::create_autocmd(
+        ["CmdlineLeave"],
+        &builder.clone().callback(on_cmdline_leave).build(),
+    )?;
+
     Ok(augroup_id)
 }
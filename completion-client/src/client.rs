@@ -3,11 +3,16 @@ use std::collections::HashMap;
 use std::ops::DerefMut;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use completion_types::{
+    AcceptedCompletion,
+    BufferEdit,
     ClientMessage,
     ClientSender,
     Clock,
+    CmdlineType,
+    CompletionItem,
     CompletionRequest,
     CoreMessage,
     CoreReceiver,
@@ -17,17 +22,24 @@ use completion_types::{
     RequestKind,
     Revision,
     ScoredCompletion,
+    SourceId,
 };
 use nvim::api::{
+    self,
     opts::{BufAttachOpts, OnBytesArgs, ShouldDetach},
     Buffer,
+    Window,
 };
 use nvim_oxi as nvim;
 
 use crate::autocmds;
 use crate::config::CompletionConfig;
+use crate::history::History;
 use crate::lateinit::LateInit;
 use crate::messages::echoerr;
+use crate::snippets::SnippetSession;
+use crate::stats::Stats;
+use crate::text_edits;
 use crate::ui::{UiConfig, UiState};
 use crate::{DocumentExt, Error, PositionExt, Result};
 
@@ -58,6 +70,9 @@ struct State {
     /// TODO: docs
     completion_config: CompletionConfig,
 
+    /// The ids of all the sources enabled in the config.
+    sources: Vec<SourceId>,
+
     /// TODO: docs
     documents: HashMap<Buffer, Arc<Document>>,
 
@@ -66,6 +81,33 @@ struct State {
 
     /// TODO: docs
     is_accepting_completions: bool,
+
+    /// Whether we're currently editing the buffer to insert an accepted
+    /// completion. The `on_bytes` callback ignores our own edits.
+    is_applying_completion: bool,
+
+    /// The snippet inserted by the last accepted completion, if it still has
+    /// tabstops to jump to.
+    snippet: Option<SnippetSession>,
+
+    /// Performance statistics displayed by `:CompletionStats`.
+    stats: Stats,
+
+    /// The position of the cursor after the last edit, used to tell apart
+    /// the cursor movements caused by editing the buffer from the ones done
+    /// by the user.
+    cursor: Option<(usize, usize)>,
+
+    /// The completions accepted in this and the previous sessions.
+    history: Option<History>,
+
+    /// The scratch buffers identifying the documents used to complete each
+    /// kind of command-line.
+    cmdline_buffers: HashMap<CmdlineType, Buffer>,
+
+    /// The contents of the command-line the last time it changed, or `None`
+    /// if we're not completing the command-line.
+    cmdline: Option<String>,
 }
 
 impl Client {
@@ -78,6 +120,7 @@ impl Client {
         core_sender: ClientSender,
         completion_config: CompletionConfig,
         ui_config: UiConfig,
+        sources: Vec<SourceId>,
     ) -> nvim::Result<()> {
         AUGROUP_ID.with(|id| id.set(augroup_id));
         CLIENT_SENDER.with(|sender| sender.set(ui_sender));
@@ -85,16 +128,22 @@ impl Client {
 
         let state = &mut *self.state.borrow_mut();
         state.completion_config = completion_config;
+        state.sources = sources;
 
         let ui_state = &mut *self.ui_state.borrow_mut();
         ui_state.init(ui_config)
     }
 
+    /// Sets the history where the accepted completions are saved.
+    pub(crate) fn set_history(&self, history: History) {
+        self.state.borrow_mut().history = Some(history);
+    }
+
     // Messages sent to the core.
 
     #[inline]
     fn send_core(&self, msg: ClientMessage) -> Result<()> {
-        CORE_SENDER.with(move |sender| sender.send(msg)).map_err(Into::into)
+        CORE_SENDER.with(move |sender| sender.send(msg).map_err(Into::into))
     }
 
     /// TODO: docs
@@ -105,30 +154,113 @@ impl Client {
         self.send_core(ClientMessage::QueryAttach { document })
     }
 
+    /// Creates the documents used to complete the command-line. They're
+    /// created upfront so that the sources are already attached to them the
+    /// first time the command-line is entered.
+    pub(crate) fn query_attach_cmdlines(&self) -> Result<()> {
+        for cmdline in [CmdlineType::Command, CmdlineType::Search] {
+            let buffer = api::create_buf(false, true)?;
+
+            self.state
+                .borrow_mut()
+                .cmdline_buffers
+                .insert(cmdline, buffer.clone());
+
+            let client_sender =
+                CLIENT_SENDER.with(|sender| (**sender).clone());
+
+            let document =
+                Document::new_cmdline(buffer, cmdline, client_sender);
+
+            self.send_core(ClientMessage::QueryAttach { document })?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the document used to complete the command-line currently
+    /// being edited, or `None` if we're not editing a command-line or if no
+    /// sources are attached to it.
+    fn cmdline_document(&self) -> Result<Option<Arc<Document>>> {
+        let cmdline = match self::cmdline_type()? {
+            Some(cmdline) => cmdline,
+            None => return Ok(None),
+        };
+
+        let state = &*self.state.borrow();
+
+        Ok(state
+            .cmdline_buffers
+            .get(&cmdline)
+            .and_then(|buffer| state.documents.get(buffer))
+            .map(Arc::clone))
+    }
+
     /// TODO: docs
     pub(crate) fn recompute_completions(
         &self,
         buffer: Buffer,
         position: Position,
+        kind: RequestKind,
         clock: Clock,
     ) -> Result<()> {
         let state = &mut *self.state.borrow_mut();
         state.revision.advance();
         state.is_accepting_completions = true;
 
-        let document = state.documents.get(&buffer).map(Arc::clone).unwrap();
+        // No sources are attached to the buffer.
+        let document = match state.documents.get(&buffer) {
+            Some(document) => Arc::clone(document),
+            None => return Ok(()),
+        };
 
         let request = CompletionRequest {
             id: state.revision,
             document,
             position,
             clock,
-            kind: RequestKind::TypedCharacter('a'),
+            kind,
         };
 
         self.send_core(ClientMessage::CompletionRequest { request })
     }
 
+    /// Asks every source to recompute its completions at the cursor, showing
+    /// them even if `menu.autoshow` is disabled.
+    pub(crate) fn show_completions(&self) -> Result<()> {
+        if let Some(document) = self.cmdline_document()? {
+            self.ui_mut().request_show();
+
+            let (line, col) = self::cmdline_cursor()?;
+            let position = Position::new(0, col as u32, line);
+
+            return self.recompute_completions(
+                document.buffer(),
+                position,
+                RequestKind::Invoked,
+                Clock::start(),
+            );
+        }
+
+        let buffer = Buffer::current();
+
+        if !self.state.borrow().documents.contains_key(&buffer) {
+            return Ok(());
+        }
+
+        self.ui_mut().request_show();
+
+        let (row, col) = Window::current().get_cursor()?;
+        let position = Position::from_row_col_buf(row - 1, col, &buffer)?;
+
+        self.recompute_completions(
+            buffer,
+            position,
+            RequestKind::Invoked,
+            Clock::start(),
+        )
+    }
+
     /// Notifies the core to stop sending completion items for the current
     /// revision even if better results become available.
     #[inline]
@@ -141,6 +273,282 @@ impl Client {
         self.send_core(msg)
     }
 
+    /// Asks the core to resolve the completion item currently selected in the
+    /// completion menu. The details window is hidden until the resolved
+    /// properties arrive.
+    pub(crate) fn resolve_selected(&self) -> Result<()> {
+        let ui = &mut *self.ui_mut();
+
+        ui.details.hide()?;
+
+        let (item, source) = match ui.menu.selected_scored() {
+            Some(selected) => (Arc::clone(&selected.item), selected.source),
+            None => return Ok(()),
+        };
+
+        let buffer = match self.cmdline_document()? {
+            Some(document) => document.buffer(),
+            None => Buffer::current(),
+        };
+
+        let state = &*self.state.borrow();
+
+        let document = match state.documents.get(&buffer) {
+            Some(document) => Arc::clone(document),
+            None => return Ok(()),
+        };
+
+        self.send_core(ClientMessage::ResolveCompletion {
+            document,
+            item,
+            source,
+            id: state.revision,
+        })
+    }
+
+    /// Stops the sources with the given names, or all of them if `names` is
+    /// empty, from providing completions in the current buffer, or in every
+    /// buffer if `all_buffers` is set.
+    pub(crate) fn detach_sources(
+        &self,
+        all_buffers: bool,
+        names: &[String],
+    ) -> Result<()> {
+        let sources = self.source_ids(names)?;
+        let buffer = (!all_buffers).then(Buffer::current);
+
+        self.stop_sending()?;
+        self.ui_mut().hide_all(&mut Buffer::current())?;
+
+        self.send_core(ClientMessage::DetachSources { buffer, sources })
+    }
+
+    /// The inverse of [`detach_sources`](Client::detach_sources).
+    pub(crate) fn reattach_sources(
+        &self,
+        all_buffers: bool,
+        names: &[String],
+    ) -> Result<()> {
+        let sources = self.source_ids(names)?;
+        let buffer = (!all_buffers).then(Buffer::current);
+        self.send_core(ClientMessage::ReattachSources { buffer, sources })
+    }
+
+    /// Returns the names of all the enabled sources.
+    pub(crate) fn source_names(&self) -> Vec<SourceId> {
+        self.state.borrow().sources.clone()
+    }
+
+    /// Converts source names to their ids, returning `None` if no names are
+    /// given.
+    fn source_ids(&self, names: &[String]) -> Result<Option<Vec<SourceId>>> {
+        if names.is_empty() {
+            return Ok(None);
+        }
+
+        let sources = &self.state.borrow().sources;
+
+        names
+            .iter()
+            .map(|name| {
+                sources
+                    .iter()
+                    .find(|&&id| id == name)
+                    .copied()
+                    .ok_or_else(|| Error::UnknownSource(name.clone()))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Some)
+    }
+
+    /// Closes the completion UI and inserts a completion item in the current
+    /// buffer.
+    pub(crate) fn accept_completion(
+        &self,
+        item: &CompletionItem,
+        source: SourceId,
+    ) -> Result<()> {
+        if let Some(document) = self.cmdline_document()? {
+            return self.accept_cmdline_completion(item, source, &document);
+        }
+
+        self.stop_sending()?;
+        self.ui_mut().hide_all(&mut Buffer::current())?;
+
+        let replace = {
+            let state = &mut *self.state.borrow_mut();
+            state.is_applying_completion = true;
+            state.completion_config.replace_on_accept
+        };
+
+        // The state can't be borrowed here since `on_bytes` is called
+        // synchronously for every edit.
+        let result = text_edits::apply_completion(item, replace);
+
+        self.state.borrow_mut().is_applying_completion = false;
+
+        let snippet = result?;
+
+        let buffer = Buffer::current();
+
+        // Record the completion under the filetype of the document, which is
+        // the one the core ranks its completions by.
+        let document_filetype = self
+            .state
+            .borrow()
+            .documents
+            .get(&buffer)
+            .map(|document| document.filetype().to_owned());
+
+        let filetype = match document_filetype {
+            Some(filetype) => filetype,
+            None => buffer.get_option("filetype")?,
+        };
+
+        let (row, col) = Window::current().get_cursor()?;
+        let position = Position::from_row_col_buf(row - 1, col, &buffer)?;
+
+        self.completion_inserted(
+            item, source, filetype, snippet, buffer, position,
+        )
+    }
+
+    /// Updates the state after a completion has been inserted in a buffer,
+    /// leaving the cursor at `position`, and recomputes the completions if
+    /// `completion.after_inserting` is set.
+    fn completion_inserted(
+        &self,
+        item: &CompletionItem,
+        source: SourceId,
+        filetype: String,
+        snippet: Option<SnippetSession>,
+        buffer: Buffer,
+        position: Position,
+    ) -> Result<()> {
+        let after_inserting = {
+            let state = &mut *self.state.borrow_mut();
+
+            self.record_accepted(state, item, source, filetype)?;
+
+            if let Some(previous) = state.snippet.take() {
+                previous.stop()?;
+            }
+
+            state.snippet = snippet;
+            state.cursor = Some((position.row as usize, position.col));
+
+            state.completion_config.after_inserting
+        };
+
+        // The state has to be released first since recomputing the
+        // completions borrows it again.
+        if !after_inserting {
+            return Ok(());
+        }
+
+        self.recompute_completions(
+            buffer,
+            position,
+            RequestKind::RecomputeAll,
+            Clock::start(),
+        )
+    }
+
+    /// Like [`accept_completion`](Client::accept_completion), but inserts the
+    /// completion in the command-line being edited.
+    fn accept_cmdline_completion(
+        &self,
+        item: &CompletionItem,
+        source: SourceId,
+        document: &Document,
+    ) -> Result<()> {
+        self.stop_sending()?;
+        self.ui_mut().hide_all(&mut Buffer::current())?;
+
+        let (line, col) = self::cmdline_cursor()?;
+
+        let (line, col, after_inserting) = {
+            let state = &mut *self.state.borrow_mut();
+
+            let (line, col) = text_edits::apply_cmdline_completion(
+                item,
+                &line,
+                col,
+                state.completion_config.replace_on_accept,
+            )?;
+
+            // The command-line is only replaced after this function returns,
+            // and that change shouldn't be treated as if the user typed it.
+            state.cmdline = Some(line.clone());
+
+            let filetype = document.filetype().to_owned();
+            self.record_accepted(state, item, source, filetype)?;
+
+            (line, col, state.completion_config.after_inserting)
+        };
+
+        if !after_inserting {
+            return Ok(());
+        }
+
+        self.recompute_completions(
+            document.buffer(),
+            Position::new(0, col as u32, line),
+            RequestKind::RecomputeAll,
+            Clock::start(),
+        )
+    }
+
+    /// Saves an accepted completion in the history and notifies the core.
+    fn record_accepted(
+        &self,
+        state: &mut State,
+        item: &CompletionItem,
+        source: SourceId,
+        filetype: String,
+    ) -> Result<()> {
+        let completion = AcceptedCompletion {
+            text: item.text.clone(),
+            source: source.to_owned(),
+            filetype,
+            timestamp: SystemTime::now(),
+        };
+
+        if let Some(history) = &mut state.history {
+            history.append(&completion)?;
+        }
+
+        self.send_core(ClientMessage::CompletionAccepted { completion })
+    }
+
+    /// Jumps to the next or previous tabstop of the current snippet,
+    /// returning whether there was a tabstop to jump to.
+    pub(crate) fn jump_snippet(&self, forward: bool) -> Result<bool> {
+        let state = &mut *self.state.borrow_mut();
+
+        let mut snippet = match state.snippet.take() {
+            Some(snippet) if *snippet.buffer() == Buffer::current() => snippet,
+            Some(snippet) => {
+                snippet.stop()?;
+                return Ok(false);
+            },
+            None => return Ok(false),
+        };
+
+        let has_jumped = snippet.jump(forward)?;
+
+        let (row, col) = Window::current().get_cursor()?;
+        state.cursor = Some((row - 1, col));
+
+        if snippet.is_finished() {
+            snippet.stop()?;
+        } else {
+            state.snippet = Some(snippet);
+        }
+
+        Ok(has_jumped)
+    }
+
     // Messages coming from the core.
 
     fn on_bytes(
@@ -156,19 +564,37 @@ impl Client {
             _cols_deleted,
             bytes_deleted,
             rows_added,
-            _cols_added,
+            cols_added,
             bytes_added,
         ): OnBytesArgs,
     ) -> Result<ShouldDetach> {
         let clock = Clock::start();
 
-        // If we've added or deleted a line we return early. If we've stayed on
-        // the same line but we've deleted characters we only continue if the
-        // `completion.while_deleting` option is set.
-        if rows_added != 0
-            || rows_deleted != 0
-            || (bytes_deleted != 0 && !false/* TODO */)
-        {
+        // The sources keeping an index of the buffer need every edit, not
+        // just the ones done in insert mode.
+        let edit =
+            self::buffer_edit(&buffer, start_row, rows_deleted, rows_added)?;
+        self.send_core(ClientMessage::BufferEdited {
+            buffer: buffer.clone(),
+            edit,
+        })?;
+
+        let end = match rows_added {
+            0 => (start_row, start_col + cols_added),
+            _ => (start_row + rows_added, cols_added),
+        };
+
+        let (is_applying_completion, while_deleting) = {
+            let state = &mut *self.state.borrow_mut();
+            state.cursor = Some(end);
+            (
+                state.is_applying_completion,
+                state.completion_config.while_deleting,
+            )
+        };
+
+        // Accepting a completion is handled by `accept_completion`.
+        if is_applying_completion {
             return Ok(false);
         }
 
@@ -177,15 +603,179 @@ impl Client {
             return Ok(false);
         }
 
-        let col = start_col + if bytes_deleted != 0 { 0 } else { bytes_added };
+        // If we've added or deleted a line the completions are stale, and the
+        // same goes for deleting characters unless the
+        // `completion.while_deleting` option is set.
+        if rows_added != 0
+            || rows_deleted != 0
+            || (bytes_deleted != 0 && bytes_added == 0 && !while_deleting)
+        {
+            self.stop_sending()?;
+            let client = self.clone();
+            nvim::schedule(move |_| {
+                client.ui_mut().hide_all(&mut Buffer::current())
+            });
+            return Ok(false);
+        }
+
+        let col = start_col + bytes_added;
 
         let position = Position::from_row_col_buf(start_row, col, &buffer)?;
 
-        self.recompute_completions(buffer, position, clock)?;
+        let kind = position
+            .line
+            .get(start_col..col)
+            .map(self::request_kind)
+            .unwrap_or(RequestKind::RecomputeAll);
+
+        self.recompute_completions(buffer, position, kind, clock)?;
 
         Ok(false)
     }
 
+    /// Called when the cursor is moved in insert mode. Moving the cursor
+    /// without editing the buffer invalidates the previous completions, so
+    /// every source is asked to recompute them.
+    pub(crate) fn on_cursor_moved(&self, buffer: Buffer) -> Result<()> {
+        let (row, col) = Window::current().get_cursor()?;
+        let cursor = (row - 1, col);
+
+        {
+            let state = &mut *self.state.borrow_mut();
+
+            if state.cursor == Some(cursor) {
+                return Ok(());
+            }
+
+            state.cursor = Some(cursor);
+        }
+
+        let position = Position::from_row_col_buf(cursor.0, col, &buffer)?;
+
+        self.recompute_completions(
+            buffer,
+            position,
+            RequestKind::RecomputeAll,
+            Clock::start(),
+        )
+    }
+
+    /// Called when the filetype of a buffer is set after the buffer was
+    /// attached to, e.g. by `:setf` or a modeline. The buffer's document is
+    /// replaced so that the next requests use the new filetype.
+    pub(crate) fn on_filetype_changed(&self, buffer: Buffer) -> Result<()> {
+        let state = &mut *self.state.borrow_mut();
+
+        let filetype = match state.documents.get(&buffer) {
+            Some(document) => document.filetype().to_owned(),
+            None => return Ok(()),
+        };
+
+        if buffer.get_option::<String>("filetype")? == filetype {
+            return Ok(());
+        }
+
+        let client_sender = CLIENT_SENDER.with(|sender| (**sender).clone());
+        let document = Document::new(buffer.clone(), client_sender)?;
+        state.documents.insert(buffer, Arc::new(document));
+
+        Ok(())
+    }
+
+    /// Called when the buffer is edited in insert mode, updating the mirrored
+    /// tabstops of the snippet being edited.
+    pub(crate) fn on_text_changed(&self, buffer: Buffer) -> Result<()> {
+        let mut snippet = {
+            let state = &mut *self.state.borrow_mut();
+
+            match state.snippet.take() {
+                Some(snippet) if *snippet.buffer() == buffer => {
+                    state.is_applying_completion = true;
+                    snippet
+                },
+                other => {
+                    state.snippet = other;
+                    return Ok(());
+                },
+            }
+        };
+
+        // The state can't be borrowed here since `on_bytes` is called
+        // synchronously for every edit.
+        let result = snippet.sync_mirrors();
+
+        let state = &mut *self.state.borrow_mut();
+        state.is_applying_completion = false;
+        state.snippet = Some(snippet);
+
+        Ok(result?)
+    }
+
+    /// Called when entering the command-line.
+    pub(crate) fn on_cmdline_enter(&self) -> Result<()> {
+        if self::cmdline_type()?.is_some() {
+            self.state.borrow_mut().cmdline = Some(String::new());
+        }
+
+        Ok(())
+    }
+
+    /// Called every time the contents of the command-line change, asking the
+    /// command-line sources to recompute their completions.
+    pub(crate) fn on_cmdline_changed(&self) -> Result<()> {
+        let clock = Clock::start();
+
+        let document = match self.cmdline_document()? {
+            Some(document) => document,
+            None => return Ok(()),
+        };
+
+        let (line, col) = self::cmdline_cursor()?;
+
+        let (previous, while_deleting) = {
+            let state = &mut *self.state.borrow_mut();
+            (
+                state.cmdline.replace(line.clone()),
+                state.completion_config.while_deleting,
+            )
+        };
+
+        // This is the case after accepting a completion, see
+        // `accept_cmdline_completion`.
+        if previous.as_deref() == Some(&*line) {
+            return Ok(());
+        }
+
+        let previous = previous.unwrap_or_default();
+
+        if line.len() < previous.len() && !while_deleting {
+            self.stop_sending()?;
+            self.ui_mut().hide_all(&mut Buffer::current())?;
+            return Ok(());
+        }
+
+        let kind = self::cmdline_request_kind(&previous, &line, col);
+
+        let position = Position::new(0, col as u32, line);
+
+        self.recompute_completions(document.buffer(), position, kind, clock)
+    }
+
+    /// Called when leaving the command-line.
+    pub(crate) fn on_cmdline_leave(&self) -> Result<()> {
+        // Leaving the expression register's prompt (e.g. the one used to
+        // accept a completion) returns to the command-line it was opened from.
+        if self::cmdline_type()?.is_none() {
+            return Ok(());
+        }
+
+        self.state.borrow_mut().cmdline = None;
+        self.stop_sending()?;
+        self.ui_mut().hide_all(&mut Buffer::current())?;
+
+        Ok(())
+    }
+
     pub(crate) fn handle_core_message(
         &self,
         receiver: &mut CoreReceiver,
@@ -210,18 +800,31 @@ impl Client {
                 },
 
                 CoreMessage::SourceCompleteFailed { source, error } => {
+                    self.state.borrow_mut().stats.record_error(source);
                     return Err(Error::SourceCompleteFailed {
                         sauce: source,
                         why: error.to_string(),
+                    });
+                },
+
+                CoreMessage::SourceResolveFailed { source, error } => {
+                    return Err(Error::SourceResolveFailed {
+                        sauce: source,
+                        why: error.to_string(),
                     })
                 },
 
                 CoreMessage::Completions { items, request, clock } => {
+                    self.state
+                        .borrow_mut()
+                        .stats
+                        .record_sources(request.id, &clock);
+
                     if self.is_last_revision(request.id) && !items.is_empty() {
                         completions = Some((
                             items,
-                            request.document.buffer(),
                             request.position.clone(),
+                            request,
                             clock,
                         ));
                     }
@@ -237,7 +840,14 @@ impl Client {
                     }
                 },
 
-                CoreMessage::ResolvedCompletion { .. } => {},
+                CoreMessage::ResolvedCompletion { item, properties, id } => {
+                    if self.is_last_revision(id) {
+                        let client = self.clone();
+                        nvim::schedule(move |_| {
+                            client.ui_mut().show_details(&item, &properties)
+                        });
+                    }
+                },
 
                 CoreMessage::CoreFailed(why) => {
                     return Err(Error::CoreFailed(why))
@@ -257,13 +867,13 @@ impl Client {
             }
         }
 
-        if let Some((items, buffer, position, clock)) = completions {
+        if let Some((items, position, request, clock)) = completions {
             let client = self.clone();
             nvim::schedule(move |_| {
                 client.update_completions(
                     items,
-                    buffer,
                     Arc::new(position),
+                    request,
                     clock,
                 )
             })
@@ -279,34 +889,35 @@ impl Client {
     fn update_completions(
         &self,
         completions: Vec<ScoredCompletion>,
-        buffer: Buffer,
         position: Arc<Position>,
+        request: Arc<CompletionRequest>,
         mut clock: Clock,
     ) -> nvim::Result<()> {
-        let len = completions.len();
+        if !self.state.borrow().is_accepting_completions {
+            return Ok(());
+        }
 
-        if self.state.borrow().is_accepting_completions {
+        {
             let ui_state = &mut *self.ui_state.borrow_mut();
-            ui_state.update_completions(completions, buffer, position)?;
+            ui_state.update_completions(
+                completions,
+                Arc::clone(&request.document),
+                position,
+            )?;
         }
 
         clock.time_ui_updated();
 
-        let [fetched, sorted, ui] = clock.report();
-
-        // #[cfg(debug_assertions)]
-        nvim::print!(
-            "{} completions fetched in {}ms, sorted in {}ms, updated ui in \
-             {}ms",
-            len,
-            fetched,
-            sorted,
-            ui
-        );
+        self.state.borrow_mut().stats.record_timings(&request.kind, &clock);
 
         Ok(())
     }
 
+    /// Returns the lines displayed by `:CompletionStats`.
+    pub(crate) fn stats(&self) -> Vec<String> {
+        self.state.borrow().stats.to_lines()
+    }
+
     /// Gives mutable access to the UI state.
     pub(crate) fn ui_mut(&self) -> impl DerefMut<Target = UiState> + '_ {
         RefMut::map(self.ui_state.borrow_mut(), |state| state)
@@ -321,6 +932,13 @@ impl Client {
 
         let buffer = document.buffer();
 
+        // The buffers of the command-line documents are never edited, and the
+        // command-line has its own autocommands.
+        if document.cmdline().is_some() {
+            state.documents.insert(buffer, document);
+            return Ok(());
+        }
+
         let opts = {
             let on_bytes = self.to_nvim_fn(Self::on_bytes);
             BufAttachOpts::builder().on_bytes(on_bytes).build()
@@ -361,3 +979,102 @@ impl Client {
         })
     }
 }
+
+/// Returns the lines replaced by an edit reported by `on_bytes`, which are
+/// the ones from the start of the edit to its end both before and after it.
+fn buffer_edit(
+    buffer: &Buffer,
+    start_row: usize,
+    rows_deleted: usize,
+    rows_added: usize,
+) -> nvim::Result<BufferEdit> {
+    let lines = buffer
+        .get_lines(start_row, start_row + rows_added + 1, false)?
+        .map(|line| line.to_string_lossy().into_owned())
+        .collect();
+
+    Ok(BufferEdit { start: start_row, replaced: rows_deleted + 1, lines })
+}
+
+/// Returns the kind of command-line currently being edited, or `None` if
+/// we're not editing one we complete.
+fn cmdline_type() -> nvim::Result<Option<CmdlineType>> {
+    let cmdtype = api::call_function::<_, String>("getcmdtype", ())?;
+    Ok(CmdlineType::from_cmdtype(&cmdtype))
+}
+
+/// Returns the contents of the command-line and the byte column of the
+/// cursor in it.
+fn cmdline_cursor() -> nvim::Result<(String, usize)> {
+    let line = api::call_function::<_, String>("getcmdline", ())?;
+    let pos = api::call_function::<_, usize>("getcmdpos", ())?;
+    Ok((line, pos.saturating_sub(1)))
+}
+
+/// Returns the kind of request caused by the command-line changing from
+/// `previous` to `line`, with the cursor now at `col`. Like in buffers,
+/// only inserting a single character at the cursor counts as typing it.
+fn cmdline_request_kind(
+    previous: &str,
+    line: &str,
+    col: usize,
+) -> RequestKind {
+    let typed = match line.get(..col).and_then(|s| s.chars().next_back()) {
+        Some(ch) => ch,
+        None => return RequestKind::RecomputeAll,
+    };
+
+    let start = col - typed.len_utf8();
+
+    let is_typed = previous.len() + typed.len_utf8() == line.len()
+        && previous.get(..start) == line.get(..start)
+        && previous.get(start..) == line.get(col..);
+
+    if is_typed {
+        RequestKind::TypedCharacter(typed)
+    } else {
+        RequestKind::RecomputeAll
+    }
+}
+
+/// Returns the kind of request caused by inserting `text`. Inserting a
+/// single character, which can span multiple bytes, is the same as typing
+/// it, while inserting more text at once (e.g. when pasting) or deleting
+/// text invalidates the completions of every source.
+fn request_kind(text: &str) -> RequestKind {
+    let mut chars = text.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => RequestKind::TypedCharacter(ch),
+        _ => RequestKind::RecomputeAll,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multibyte_character_is_typed() {
+        assert!(matches!(request_kind("é"), RequestKind::TypedCharacter('é')));
+    }
+
+    #[test]
+    fn pasting_recomputes_all() {
+        assert!(matches!(request_kind("foo.bar"), RequestKind::RecomputeAll));
+        assert!(matches!(request_kind(""), RequestKind::RecomputeAll));
+    }
+
+    #[test]
+    fn typing_in_cmdline() {
+        assert!(matches!(
+            cmdline_request_kind("ed foo", "edi foo", 3),
+            RequestKind::TypedCharacter('i')
+        ));
+
+        assert!(matches!(
+            cmdline_request_kind("ed foo", "edit foo", 4),
+            RequestKind::RecomputeAll
+        ));
+    }
+}
@@ -1,9 +1,11 @@
 use crate::{Client, Result};
 
+/// Enables completions in the current buffer, or in every buffer if `bang`
+/// is set. If any source names are given only those sources are enabled.
 pub(super) fn completion_start(
     client: &Client,
     bang: bool,
     sources: Vec<String>,
 ) -> Result<()> {
-    Ok(())
+    client.reattach_sources(bang, &sources)
 }
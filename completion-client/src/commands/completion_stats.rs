@@ -1,7 +1,40 @@
+use std::cmp;
+
+use nvim::api::{
+    self,
+    types::{WindowConfig, WindowRelativeTo},
+};
 use nvim_oxi as nvim;
 
 use crate::{Client, Result};
 
+/// Opens a floating window summarizing the performance of the completion
+/// pipeline. The window is entered so that it can be closed with `:q`.
 pub(super) fn completion_stats(client: &Client) -> Result<()> {
+    let lines = client.stats();
+
+    let mut buf = api::create_buf(false, true)?;
+    buf.set_lines(0, usize::MAX, false, lines.iter().map(|line| &**line))?;
+    buf.set_option("bufhidden", "wipe")?;
+    buf.set_option("modifiable", false)?;
+
+    let columns = api::get_option::<u16>("columns")?;
+    let rows = api::get_option::<u16>("lines")?;
+
+    let width = lines.iter().map(|line| line.len()).max().unwrap_or(0) as u16;
+    let width = cmp::min(width, columns.saturating_sub(4)).max(1);
+    let height = cmp::min(lines.len() as u16, rows.saturating_sub(4)).max(1);
+
+    let config = WindowConfig::builder()
+        .relative(WindowRelativeTo::Editor)
+        .height(height as _)
+        .width(width as _)
+        .row((rows - height) / 2)
+        .col((columns - width) / 2)
+        .zindex(250)
+        .build();
+
+    api::open_win(&buf, true, &config)?;
+
     Ok(())
 }
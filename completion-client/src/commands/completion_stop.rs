@@ -1,9 +1,11 @@
 use crate::{Client, Result};
 
+/// Disables completions in the current buffer, or in every buffer if `bang`
+/// is set. If any source names are given only those sources are disabled.
 pub(super) fn completion_stop(
     client: &Client,
     bang: bool,
     sources: Vec<String>,
 ) -> Result<()> {
-    Ok(())
+    client.detach_sources(bang, &sources)
 }
@@ -1,7 +1,7 @@
 use nvim_oxi::api::{
     self,
     opts::CreateCommandOpts,
-    types::{CommandArgs, CommandNArgs},
+    types::{CommandArgs, CommandComplete, CommandNArgs},
 };
 
 use crate::{Client, Result};
@@ -18,9 +18,17 @@ pub(crate) fn setup(client: &Client) -> Result<()> {
         super::completion_stop(client, args.bang, args.fargs)
     });
 
+    let complete = client.to_nvim_fn(
+        |client,
+         (arg_lead, cmd_line, _cursor_pos): (String, String, usize)| {
+            Ok(self::complete_source_names(client, &arg_lead, &cmd_line))
+        },
+    );
+
     let opts = CreateCommandOpts::builder()
         .bang(true)
         .nargs(CommandNArgs::Any)
+        .complete(CommandComplete::CustomList(complete))
         .build();
 
     api::create_user_command("CompletionStats", stats, &Default::default())?;
@@ -29,3 +37,26 @@ pub(crate) fn setup(client: &Client) -> Result<()> {
 
     Ok(())
 }
+
+/// Completes the names of the enabled sources that start with `arg_lead` and
+/// aren't already in the command line.
+fn complete_source_names(
+    client: &Client,
+    arg_lead: &str,
+    cmd_line: &str,
+) -> Vec<String> {
+    let already_given =
+        cmd_line.split_whitespace().skip(1).collect::<Vec<_>>();
+
+    let mut names = client
+        .source_names()
+        .into_iter()
+        .filter(|name| name.starts_with(arg_lead))
+        .filter(|name| !already_given.contains(name))
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+
+    names.sort_unstable();
+
+    names
+}
@@ -3,6 +3,8 @@ use std::ops::RangeInclusive;
 
 use completion_types::CompletionItem;
 
+use crate::hlgroups;
+
 pub(crate) trait CompletionItemExt {
     fn highlight_ranges(&self) -> Vec<(RangeInclusive<usize>, &'static str)>;
     fn hint_display(&self) -> Cow<'_, str>;
@@ -18,15 +20,69 @@ impl CompletionItemExt for CompletionItem {
         }
     }
 
+    /// The line displayed in the completion menu, made of the text of the
+    /// item followed by its detail, its label description and its kind, all
+    /// separated by a single space.
     fn menu_display(&self) -> String {
-        format!(" {} ", self.hint_display())
+        let mut display = format!(" {}", self.hint_display());
+
+        for (extra, _) in self::menu_extras(self) {
+            display.push(' ');
+            display.push_str(extra);
+        }
+
+        display.push(' ');
+        display
     }
 
     fn text_offset(&self) -> usize {
         1
     }
 
+    /// The byte ranges of the [`menu_display`](Self::menu_display) to
+    /// highlight together with their highlight group. Like the ranges
+    /// returned by [`completion_types::ScoredCompletion::matched_ranges`],
+    /// their end is exclusive.
     fn highlight_ranges(&self) -> Vec<(RangeInclusive<usize>, &'static str)> {
-        vec![]
+        let mut ranges = Vec::new();
+
+        let mut offset = self.text_offset() + self.hint_display().len();
+
+        if self.is_deprecated {
+            ranges.push((
+                self.text_offset()..=offset,
+                hlgroups::MENU_DEPRECATED,
+            ));
+        }
+
+        for (extra, hl_group) in self::menu_extras(self) {
+            // Skip the separating space.
+            offset += 1;
+
+            ranges.push((offset..=offset + extra.len(), hl_group));
+
+            offset += extra.len();
+        }
+
+        ranges
     }
 }
+
+/// Returns the detail, the label description and the name of the kind of a
+/// completion item together with their highlight groups, skipping the ones
+/// that are not set.
+fn menu_extras(
+    item: &CompletionItem,
+) -> impl Iterator<Item = (&str, &'static str)> + '_ {
+    // Only the first line of the detail is shown in the menu.
+    let detail = item.detail.as_deref().and_then(|d| d.lines().next());
+
+    [
+        (detail, hlgroups::MENU_DETAIL),
+        (item.label_description.as_deref(), hlgroups::MENU_DETAIL),
+        (item.kind.map(|kind| kind.name()), hlgroups::MENU_KIND),
+    ]
+    .into_iter()
+    .filter_map(|(extra, hl_group)| Some((extra?, hl_group)))
+    .filter(|(extra, _)| !extra.is_empty())
+}
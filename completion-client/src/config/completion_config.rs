@@ -1,3 +1,9 @@
+use completion_types::{
+    CaseMatching,
+    MatcherConfig,
+    MatcherKind,
+    RankingWeights,
+};
 use serde::Deserialize;
 
 #[derive(Deserialize)]
@@ -6,15 +12,75 @@ pub(crate) struct CompletionConfig {
     /// Whether to show completion results right after a completion has been
     /// accepted. If `false`, after accepting a completion new results will
     /// only be shown after typing more characters.
-    pub(super) after_inserting: bool,
+    pub(crate) after_inserting: bool,
 
     /// Whether to show completion results while deleting. If set to `false`
     /// completions will only be shown automatically when inserting characters.
-    pub(super) while_deleting: bool,
+    pub(crate) while_deleting: bool,
+
+    /// Whether accepting a completion should also replace the rest of the
+    /// word after the cursor, for the sources that support it. If `false`
+    /// the completion is inserted at the cursor.
+    #[serde(default)]
+    pub(crate) replace_on_accept: bool,
+
+    /// The algorithm used to filter and score the completions against the
+    /// prefix typed by the user, one of `"fuzzy"`, `"prefix"`,
+    /// `"substring"`, `"fzf-v2"` or `"exact"`.
+    #[serde(default)]
+    matcher: MatcherKind,
+
+    /// Whether the matcher should `"respect"` or `"ignore"` case, or only
+    /// respect it if the prefix contains an upper case character
+    /// (`"smart"`).
+    #[serde(default)]
+    case: CaseMatching,
+
+    /// The maximum number of mistyped characters a prefix can contain while
+    /// still matching a completion, e.g. `"fxo"` matches `"foo"` with one
+    /// typo. Missing or extra characters don't count as typos, and at least
+    /// one character has to be typed correctly.
+    #[serde(default)]
+    max_typos: u8,
+
+    /// The weights used to rank the completions matching the prefix, e.g.
+    /// `{ score = 1, source_priority = 0.5, frecency = 0 }`.
+    #[serde(default)]
+    pub(crate) ranking: RankingWeights,
+
+    /// Whether to merge the completions with the same insert text provided
+    /// by different sources, keeping the one of the source with the highest
+    /// priority.
+    #[serde(default = "yes")]
+    pub(crate) dedup: bool,
 }
 
 impl Default for CompletionConfig {
     fn default() -> Self {
-        Self { after_inserting: false, while_deleting: false }
+        Self {
+            after_inserting: false,
+            while_deleting: false,
+            replace_on_accept: false,
+            matcher: MatcherKind::default(),
+            case: CaseMatching::default(),
+            max_typos: 0,
+            ranking: RankingWeights::default(),
+            dedup: yes(),
+        }
     }
 }
+
+impl CompletionConfig {
+    /// The part of the config used by the core to filter the completions.
+    pub(crate) fn matcher_config(&self) -> MatcherConfig {
+        MatcherConfig {
+            kind: self.matcher,
+            case: self.case,
+            max_typos: self.max_typos,
+        }
+    }
+}
+
+fn yes() -> bool {
+    true
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::fmt;
 
-use completion_types::SourceEnable;
+use completion_types::{SourceEnable, SourceLimits};
 use nvim_oxi::Object;
 use serde::de::{Deserializer, Error, MapAccess, Visitor};
 use serde::Deserialize;
@@ -14,10 +14,40 @@ pub(crate) type SourcesConfig = HashMap<String, SourceConfig>;
 pub(crate) struct SourceConfig {
     pub(crate) enable: SourceEnable,
 
+    /// How the completions of this source are ranked relative to the ones
+    /// of the other sources, the higher the better.
+    #[serde(default)]
+    pub(crate) priority: i32,
+
+    /// The maximum number of completions of this source shown at once.
+    #[serde(default)]
+    max_items: Option<usize>,
+
+    /// The minimum number of characters the prefix has to contain before
+    /// this source is asked for completions.
+    #[serde(default)]
+    min_prefix_length: usize,
+
+    /// The minimum number of characters of the completions of this source.
+    /// Shorter completions are discarded.
+    #[serde(default)]
+    keyword_length: usize,
+
     #[serde(flatten)]
     pub(crate) rest: Object,
 }
 
+impl SourceConfig {
+    #[inline]
+    pub(crate) fn limits(&self) -> SourceLimits {
+        SourceLimits {
+            max_items: self.max_items,
+            min_prefix_length: self.min_prefix_length,
+            keyword_length: self.keyword_length,
+        }
+    }
+}
+
 pub(super) fn deserialize<'de, D>(d: D) -> Result<SourcesConfig, D::Error>
 where
     D: Deserializer<'de>,
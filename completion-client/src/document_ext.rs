@@ -1,8 +1,14 @@
-use completion_types::{CoreSender, Document};
+use completion_types::{CmdlineType, CoreSender, Document};
 use nvim_oxi::{self as nvim, api::Buffer};
 
 pub(crate) trait DocumentExt: Sized {
     fn new(buffer: Buffer, ui_sender: CoreSender) -> nvim::Result<Self>;
+
+    fn new_cmdline(
+        buffer: Buffer,
+        cmdline: CmdlineType,
+        ui_sender: CoreSender,
+    ) -> Self;
 }
 
 impl DocumentExt for Document {
@@ -10,6 +16,30 @@ impl DocumentExt for Document {
     #[inline(always)]
     fn new(buffer: Buffer, ui_sender: CoreSender) -> nvim::Result<Self> {
         let path = buffer.get_name()?;
-        Ok(Self { buffer, path, client_sender: ui_sender })
+        let filetype = buffer.get_option::<String>("filetype")?;
+        Ok(Self {
+            buffer,
+            path,
+            filetype,
+            cmdline: None,
+            client_sender: ui_sender,
+        })
+    }
+
+    /// Creates the document used to complete a kind of command-line, whose
+    /// buffer is a scratch buffer only used to identify it.
+    #[inline(always)]
+    fn new_cmdline(
+        buffer: Buffer,
+        cmdline: CmdlineType,
+        ui_sender: CoreSender,
+    ) -> Self {
+        Self {
+            buffer,
+            path: Default::default(),
+            filetype: cmdline.as_str().to_owned(),
+            cmdline: Some(cmdline),
+            client_sender: ui_sender,
+        }
     }
 }
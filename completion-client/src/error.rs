@@ -29,6 +29,12 @@ pub(crate) enum Error {
     #[error("source `{sauce}` failed to compute completions: {why}")]
     SourceEnableFailed { sauce: SourceId, why: String },
 
+    #[error("source `{sauce}` failed to resolve completion: {why}")]
+    SourceResolveFailed { sauce: SourceId, why: String },
+
+    #[error("there's no source named `{0}`")]
+    UnknownSource(String),
+
     #[error(transparent)]
     NvimLoop(#[from] nvim_oxi::libuv::Error),
 
@@ -44,13 +50,15 @@ pub(crate) enum Error {
     #[error(transparent)]
     OneshotRecv(#[from] tokio::sync::oneshot::error::RecvError),
 
+    // Boxed since the unsent message can be quite large.
     #[error(transparent)]
-    ClientSendError(
-        #[from] tokio::sync::mpsc::error::SendError<ClientMessage>,
-    ),
+    ClientSendError(Box<tokio::sync::mpsc::error::SendError<ClientMessage>>),
 
     #[error(transparent)]
     Loading(#[from] libloading::Error),
+
+    #[error("couldn't access the completion history: {0}")]
+    History(#[from] std::io::Error),
 }
 
 impl From<serde_path_to_error::Error<nvim::serde::Error>> for Error {
@@ -63,6 +71,12 @@ impl From<serde_path_to_error::Error<nvim::serde::Error>> for Error {
     }
 }
 
+impl From<tokio::sync::mpsc::error::SendError<ClientMessage>> for Error {
+    fn from(err: tokio::sync::mpsc::error::SendError<ClientMessage>) -> Self {
+        Self::ClientSendError(Box::new(err))
+    }
+}
+
 impl Error {
     #[inline]
     pub(crate) fn is_fatal(&self) -> bool {
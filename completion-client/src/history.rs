@@ -0,0 +1,157 @@
+//! Persists the completions accepted by the user across sessions, so that the
+//! core can keep ranking them higher after a restart.
+//!
+//! The history is stored in `stdpath("data")/nvim-completion/history`, one
+//! completion per line with its timestamp, source, filetype and text
+//! separated by tabs.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, UNIX_EPOCH};
+
+use completion_types::AcceptedCompletion;
+use nvim_oxi::api;
+
+use crate::Result;
+
+/// The maximum number of completions kept in the history. The oldest ones are
+/// dropped when the history is loaded.
+const MAX_ENTRIES: usize = 10_000;
+
+pub(crate) struct History {
+    file: File,
+}
+
+impl History {
+    /// Opens the history file, creating it if it doesn't exist, and returns
+    /// it together with the completions it contains.
+    ///
+    /// Has to be called on the Neovim thread.
+    pub(crate) fn load() -> Result<(Self, Vec<AcceptedCompletion>)> {
+        let path = self::path()?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut completions = match fs::read(&path) {
+            Ok(contents) => self::parse_history(&contents),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                Vec::new()
+            },
+            Err(err) => return Err(err.into()),
+        };
+
+        // Rewrite the file without the oldest completions to keep it from
+        // growing forever.
+        if completions.len() > MAX_ENTRIES {
+            completions.drain(..completions.len() - MAX_ENTRIES);
+
+            let contents = completions
+                .iter()
+                .filter_map(self::format_line)
+                .collect::<String>();
+
+            fs::write(&path, contents)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok((Self { file }, completions))
+    }
+
+    /// Appends an accepted completion to the history.
+    pub(crate) fn append(
+        &mut self,
+        completion: &AcceptedCompletion,
+    ) -> Result<()> {
+        if let Some(line) = self::format_line(completion) {
+            self.file.write_all(line.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+fn path() -> Result<PathBuf> {
+    let data = api::call_function::<_, String>("stdpath", ("data",))?;
+    Ok([&*data, "nvim-completion", "history"].iter().collect())
+}
+
+/// Formats a completion as a line of the history file, or returns `None` if
+/// one of its fields contains a tab or a newline.
+fn format_line(completion: &AcceptedCompletion) -> Option<String> {
+    let AcceptedCompletion { text, source, filetype, timestamp } = completion;
+
+    let fields = [text, source, filetype];
+
+    if fields.iter().any(|field| field.contains(['\t', '\n'])) {
+        return None;
+    }
+
+    let secs = timestamp.duration_since(UNIX_EPOCH).ok()?.as_secs();
+
+    Some(format!("{secs}\t{source}\t{filetype}\t{text}\n"))
+}
+
+/// Parses the contents of the history file, skipping the lines that can't be
+/// parsed instead of failing on the first invalid byte.
+fn parse_history(contents: &[u8]) -> Vec<AcceptedCompletion> {
+    contents
+        .split(|&byte| byte == b'\n')
+        .filter_map(|line| self::parse_line(&String::from_utf8_lossy(line)))
+        .collect()
+}
+
+fn parse_line(line: &str) -> Option<AcceptedCompletion> {
+    let mut fields = line.splitn(4, '\t');
+
+    let secs = fields.next()?.parse::<u64>().ok()?;
+    let source = fields.next()?.to_owned();
+    let filetype = fields.next()?.to_owned();
+    let text = fields.next()?.to_owned();
+
+    Some(AcceptedCompletion {
+        text,
+        source,
+        filetype,
+        timestamp: UNIX_EPOCH + Duration::from_secs(secs),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_then_parse() {
+        let completion = AcceptedCompletion {
+            text: "foo bar".to_owned(),
+            source: "lsp".to_owned(),
+            filetype: "rust".to_owned(),
+            timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        };
+
+        let line = format_line(&completion).unwrap();
+        assert_eq!(Some(completion), parse_line(line.trim_end()));
+
+        let completion = AcceptedCompletion {
+            text: "foo\tbar".to_owned(),
+            ..parse_line(line.trim_end()).unwrap()
+        };
+        assert_eq!(None, format_line(&completion));
+    }
+
+    #[test]
+    fn skip_corrupt_lines() {
+        let contents =
+            b"1\tlsp\trust\tfoo\n\xff\xfe\n2\tlsp\trust\tb\xffr\nbar\n";
+
+        let texts = parse_history(contents)
+            .into_iter()
+            .map(|completion| completion.text)
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec!["foo", "b\u{fffd}r"], texts);
+    }
+}
@@ -10,6 +10,9 @@ pub(crate) fn setup() -> nvim::Result<()> {
     api::set_hl(0, MENU_MATCHING, &opts.bold(true).build())?;
     opts.bold(false);
 
+    api::set_hl(0, MENU_DEPRECATED, &opts.strikethrough(true).build())?;
+    opts.strikethrough(false);
+
     api::set_hl(0, BAD_OPTION_PATH, &opts.link("Statement").build())?;
     api::set_hl(0, ERROR_MSG_TAG, &opts.link("ErrorMsg").build())?;
     api::set_hl(0, INFO_MSG_TAG, &opts.link("Question").build())?;
@@ -21,7 +24,11 @@ pub(crate) fn setup() -> nvim::Result<()> {
     api::set_hl(0, HINT, &opts.link("Comment").build())?;
     api::set_hl(0, MENU, &opts.link("NormalFloat").build())?;
     api::set_hl(0, MENU_BORDER, &opts.link("FloatBorder").build())?;
+    api::set_hl(0, MENU_DETAIL, &opts.link("Comment").build())?;
+    api::set_hl(0, MENU_KIND, &opts.link("Type").build())?;
     api::set_hl(0, MENU_SELECTED, &opts.link("PmenuSel").build())?;
+    api::set_hl(0, MENU_SOURCES, &opts.link("Special").build())?;
+    api::set_hl(0, SNIPPET_TABSTOP, &opts.link("Visual").build())?;
 
     Ok(())
 }
@@ -66,11 +73,29 @@ mod consts {
         /// Highlights the border of the completion menu.
         pub const MENU_BORDER: &str = "CompletionMenuBorder";
 
+        /// Highlights deprecated completion items.
+        pub const MENU_DEPRECATED: &str = "CompletionMenuDeprecated";
+
+        /// Highlights the detail and the label description of the completion
+        /// items.
+        pub const MENU_DETAIL: &str = "CompletionMenuDetail";
+
+        /// Highlights the kind of the completion items.
+        pub const MENU_KIND: &str = "CompletionMenuKind";
+
         /// Highlights the characters where a completion item matches the
         /// current completion prefix.
         pub const MENU_MATCHING: &str = "CompletionMenuMatchingChars";
 
         /// Highlights the currently selected completion item.
         pub const MENU_SELECTED: &str = "CompletionMenuSelected";
+
+        /// Highlights the names of the sources that provided the completion
+        /// items.
+        pub const MENU_SOURCES: &str = "CompletionMenuSources";
+
+        /// Highlights the placeholders of the tabstops of an expanded
+        /// snippet.
+        pub const SNIPPET_TABSTOP: &str = "CompletionSnippetTabstop";
     }
 }
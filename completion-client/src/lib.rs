@@ -5,13 +5,17 @@ mod completion_item_ext;
 mod config;
 mod document_ext;
 mod error;
+mod history;
 mod hlgroups;
 mod lateinit;
 mod mappings;
 mod messages;
 mod position_ext;
 mod setup;
+mod snippets;
 mod source_bundle_ext;
+mod stats;
+mod text_edits;
 mod ui;
 mod utils;
 
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use super::IdentifyCompletion;
 use crate::{Client, Result};
 
@@ -5,5 +7,24 @@ pub(super) fn accept_completion(
     client: &Client,
     which: IdentifyCompletion,
 ) -> Result<()> {
-    Ok(())
+    let (item, source) = {
+        let menu = &client.ui_mut().menu;
+
+        let idx = match which {
+            IdentifyCompletion::ByIndex(idx) => Some(idx),
+
+            IdentifyCompletion::FromSelected(offset) => menu
+                .selected_index()
+                .and_then(|idx| idx.checked_add_signed(offset)),
+        };
+
+        match idx.and_then(|idx| menu.nth_scored(idx)) {
+            Some(completion) => {
+                (Arc::clone(&completion.item), completion.source)
+            },
+            None => return Ok(()),
+        }
+    };
+
+    client.accept_completion(&item, source)
 }
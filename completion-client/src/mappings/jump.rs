@@ -0,0 +1,8 @@
+use crate::{Client, Result};
+
+/// Jumps to the next or previous tabstop of the last expanded snippet,
+/// returning whether there was a tabstop to jump to. This lets users fall
+/// back to another action, e.g. inserting a `<Tab>`, when there isn't one.
+pub(super) fn jump(client: &Client, forward: bool) -> Result<bool> {
+    client.jump_snippet(forward)
+}
@@ -1,12 +1,14 @@
 //! TODO: docs
 
 mod accept_completion;
+mod jump;
 mod scroll_details;
 mod select_completion;
 mod setup;
 mod show_completions;
 
 use accept_completion::accept_completion;
+use jump::jump;
 use scroll_details::scroll_details;
 use select_completion::select_completion;
 pub(crate) use setup::setup;
@@ -1,5 +1,5 @@
 use crate::{Client, Result};
 
 pub(super) fn scroll_details(client: &Client, lines: i32) -> Result<()> {
-    Ok(())
+    client.ui_mut().details.scroll(lines).map_err(Into::into)
 }
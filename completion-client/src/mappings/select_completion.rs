@@ -5,12 +5,30 @@ pub(super) fn select_completion(
     client: &Client,
     which: IdentifyCompletion,
 ) -> Result<()> {
-    let ui = &mut *client.ui_mut();
+    {
+        let ui = &mut *client.ui_mut();
 
-    match which {
-        IdentifyCompletion::ByIndex(idx) => todo!(),
-        IdentifyCompletion::FromSelected(offset) => todo!(),
+        match which {
+            IdentifyCompletion::ByIndex(idx) => {
+                ui.menu.select_completion(Some(idx))?
+            },
+
+            IdentifyCompletion::FromSelected(1) => ui.menu.select_next()?,
+
+            IdentifyCompletion::FromSelected(-1) => ui.menu.select_prev()?,
+
+            IdentifyCompletion::FromSelected(offset) => {
+                let idx = ui
+                    .menu
+                    .selected_index()
+                    .and_then(|idx| idx.checked_add_signed(offset));
+
+                ui.menu.select_completion(idx)?
+            },
+        }
+
+        ui.update_hint()?;
     }
 
-    Ok(())
+    client.resolve_selected()
 }
@@ -33,9 +33,15 @@ pub(crate) fn setup(
 
     let show = client.to_nvim_fn(|client, ()| super::show_completions(client));
 
+    let jump_next = client.to_nvim_fn(|client, ()| super::jump(client, true));
+
+    let jump_prev = client.to_nvim_fn(|client, ()| super::jump(client, false));
+
     [
         ("accept_first", Object::from(accept_first)),
         ("accept_selected", Object::from(accept_selected)),
+        ("jump_next", Object::from(jump_next)),
+        ("jump_prev", Object::from(jump_prev)),
         ("scroll_details", Object::from(scroll_details)),
         ("select_next", Object::from(select_next)),
         ("select_prev", Object::from(select_prev)),
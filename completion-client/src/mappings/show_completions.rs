@@ -1,5 +1,5 @@
 use crate::{Client, Result};
 
 pub(super) fn show_completions(client: &Client) -> Result<()> {
-    Ok(())
+    client.show_completions()
 }
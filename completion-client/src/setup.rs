@@ -21,7 +21,9 @@ use once_cell::unsync::Lazy;
 use tokio::sync::mpsc;
 
 use crate::autocmds;
+use crate::commands;
 use crate::config::{Config, SourceConfig, SourcesConfig};
+use crate::history::History;
 use crate::hlgroups;
 use crate::messages::echoerr;
 use crate::{Client, Error, Result, SourceBundleExt};
@@ -127,14 +129,48 @@ fn setup(client: &Client, preferences: Object) -> Result<()> {
         sources.into_iter().map(|(_id, bundle)| bundle).collect::<Vec<_>>()
     };
 
+    // A history that can't be loaded shouldn't keep the plugin from starting.
+    let (history, accepted) = match History::load() {
+        Ok((history, accepted)) => (Some(history), accepted),
+        Err(err) => {
+            echoerr!("couldn't load the completion history: {}", err);
+            (None, Vec::new())
+        },
+    };
+
+    let source_ids = sources.iter().map(|bundle| bundle.id).collect();
+
     let augroup_id = autocmds::setup(client)?;
 
     let core_sender = self::register_main_callback(client.clone())?;
 
     let (client_sender, client_receiver) = mpsc::unbounded_channel();
-    completion_core::start(sources, core_sender.clone(), client_receiver);
+    completion_core::start(
+        sources,
+        completion.matcher_config(),
+        completion.ranking,
+        completion.dedup,
+        accepted,
+        core_sender.clone(),
+        client_receiver,
+    );
+
+    client.init(
+        augroup_id,
+        core_sender,
+        client_sender,
+        completion,
+        ui,
+        source_ids,
+    )?;
+
+    if let Some(history) = history {
+        client.set_history(history);
+    }
+
+    client.query_attach_cmdlines()?;
 
-    client.init(augroup_id, core_sender, client_sender, completion, ui)?;
+    commands::setup(client)?;
 
     Ok(())
 }
@@ -144,7 +180,10 @@ fn filter_enabled(
     sources: &mut HashMap<SourceId, SourceBundle>,
     configs: SourcesConfig,
 ) -> Result<()> {
-    for (name, SourceConfig { enable, rest }) in configs {
+    for (name, config) in configs {
+        let limits = config.limits();
+        let SourceConfig { enable, priority, rest, .. } = config;
+
         match enable {
             SourceEnable::Bool(false) => continue,
 
@@ -152,6 +191,8 @@ fn filter_enabled(
                 let bundle = sources.get_mut(&*name).unwrap();
                 bundle.set_config(rest)?;
                 bundle.set_enable(enable);
+                bundle.set_priority(priority);
+                bundle.set_limits(limits);
             },
         }
     }
@@ -0,0 +1,162 @@
+use std::ops::Range;
+
+use super::Element;
+
+/// The result of expanding a parsed snippet.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Expansion {
+    /// The text to insert in the buffer.
+    pub(crate) text: String,
+
+    /// The tabstops of the snippet in the order they should be visited, i.e.
+    /// sorted by index with the final `$0` tabstop last. There's always a
+    /// final tabstop, placed at the end of the text if the snippet doesn't
+    /// define one.
+    pub(crate) tabstops: Vec<Tabstop>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Tabstop {
+    pub(crate) index: u32,
+
+    /// The byte ranges into the expanded text of all the occurrences of the
+    /// tabstop.
+    pub(crate) ranges: Vec<Range<usize>>,
+}
+
+/// Expands a parsed snippet, adding `indent` after every newline and
+/// replacing variables with the values returned by `resolve`.
+pub(crate) fn expand<F>(
+    elements: &[Element],
+    indent: &str,
+    resolve: F,
+) -> Expansion
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut expander = Expander {
+        text: String::new(),
+        tabstops: Vec::new(),
+        indent,
+        resolve,
+    };
+
+    expander.expand(elements);
+
+    let Expander { text, mut tabstops, .. } = expander;
+
+    if !tabstops.iter().any(|tabstop| tabstop.index == 0) {
+        tabstops.push(Tabstop {
+            index: 0,
+            ranges: vec![Range { start: text.len(), end: text.len() }],
+        });
+    }
+
+    // `0` is the final tabstop so it's visited last.
+    tabstops.sort_by_key(|tabstop| tabstop.index.wrapping_sub(1));
+
+    Expansion { text, tabstops }
+}
+
+struct Expander<'a, F> {
+    text: String,
+    tabstops: Vec<Tabstop>,
+    indent: &'a str,
+    resolve: F,
+}
+
+impl<F> Expander<'_, F>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    fn expand(&mut self, elements: &[Element]) {
+        for element in elements {
+            match element {
+                Element::Text(text) => self.push_text(text),
+
+                Element::Tabstop { index, placeholder } => {
+                    let start = self.text.len();
+                    self.expand(placeholder);
+                    self.add_range(*index, start..self.text.len());
+                },
+
+                Element::Choice { index, options } => {
+                    let start = self.text.len();
+                    if let Some(first) = options.first() {
+                        self.push_text(first);
+                    }
+                    self.add_range(*index, start..self.text.len());
+                },
+
+                Element::Variable { name, default } => {
+                    match (self.resolve)(name) {
+                        Some(value) => self.push_text(&value),
+                        None if !default.is_empty() => self.expand(default),
+                        None => self.push_text(name),
+                    }
+                },
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let mut lines = text.split('\n');
+
+        if let Some(first) = lines.next() {
+            self.text.push_str(first);
+        }
+
+        for line in lines {
+            self.text.push('\n');
+            self.text.push_str(self.indent);
+            self.text.push_str(line);
+        }
+    }
+
+    fn add_range(&mut self, index: u32, range: Range<usize>) {
+        match self.tabstops.iter_mut().find(|tabstop| tabstop.index == index) {
+            Some(tabstop) => tabstop.ranges.push(range),
+            None => self.tabstops.push(Tabstop { index, ranges: vec![range] }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse;
+    use super::*;
+
+    fn expand_str(snippet: &str) -> Expansion {
+        expand(&parse(snippet), "  ", |name| {
+            (name == "TM_FILENAME").then(|| "foo.rs".to_owned())
+        })
+    }
+
+    #[test]
+    fn final_tabstop_added() {
+        let expansion = expand_str("foo(${1:bar})");
+        assert_eq!("foo(bar)", expansion.text);
+        assert_eq!(
+            vec![
+                Tabstop { index: 1, ranges: vec![Range { start: 4, end: 7 }] },
+                Tabstop { index: 0, ranges: vec![Range { start: 8, end: 8 }] },
+            ],
+            expansion.tabstops
+        );
+    }
+
+    #[test]
+    fn final_tabstop_last() {
+        let expansion = expand_str("$0 ${2:b} $1 $2");
+        let indices =
+            expansion.tabstops.iter().map(|t| t.index).collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 0], indices);
+        assert_eq!(vec![1..2, 4..4], expansion.tabstops[1].ranges);
+    }
+
+    #[test]
+    fn indent_and_variables() {
+        let expansion = expand_str("// $TM_FILENAME\nfn ${FOO:main}() {\n}");
+        assert_eq!("// foo.rs\n  fn main() {\n  }", expansion.text);
+    }
+}
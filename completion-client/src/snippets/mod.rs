@@ -0,0 +1,22 @@
+//! Expansion of the snippets inserted by completion items.
+
+mod expansion;
+mod parser;
+mod session;
+mod variables;
+
+pub(crate) use expansion::{expand, Expansion};
+use parser::parse;
+pub(crate) use parser::Element;
+pub(crate) use session::SnippetSession;
+pub(crate) use variables::VariableContext;
+
+/// Parses and expands a snippet.
+#[inline]
+pub(crate) fn expand_snippet(
+    snippet: &str,
+    indent: &str,
+    context: &VariableContext<'_>,
+) -> Expansion {
+    self::expand(&self::parse(snippet), indent, |name| context.resolve(name))
+}
@@ -0,0 +1,355 @@
+//! A parser for the snippet syntax defined by the Language Server Protocol,
+//! which is itself a subset of the TextMate one.
+//!
+//! ```text
+//! any         ::= tabstop | placeholder | choice | variable | text
+//! tabstop     ::= '$' int | '${' int '}'
+//! placeholder ::= '${' int ':' any '}'
+//! choice      ::= '${' int '|' text (',' text)* '|}'
+//! variable    ::= '$' var | '${' var '}' | '${' var ':' any '}'
+//!               | '${' var '/' regex '/' format '/' options '}'
+//! ```
+//!
+//! Parsing never fails: anything that's not valid snippet syntax is treated
+//! as text.
+
+/// A single element of a parsed snippet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Element {
+    Text(String),
+
+    /// A tabstop, possibly with a placeholder (which is empty for `$1` and
+    /// `${1}`).
+    Tabstop {
+        index: u32,
+        placeholder: Vec<Element>,
+    },
+
+    /// A tabstop offering a list of values to choose from.
+    Choice {
+        index: u32,
+        options: Vec<String>,
+    },
+
+    /// A variable, possibly with a default value used if the variable is not
+    /// set. Transforms are parsed but ignored.
+    Variable {
+        name: String,
+        default: Vec<Element>,
+    },
+}
+
+/// Parses a snippet.
+pub(crate) fn parse(snippet: &str) -> Vec<Element> {
+    let mut parser = Parser { chars: snippet.chars().collect(), pos: 0 };
+    parser.parse_any(false)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    #[inline]
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    #[inline]
+    fn eat(&mut self, char: char) -> bool {
+        let is_next = self.peek() == Some(char);
+        if is_next {
+            self.pos += 1;
+        }
+        is_next
+    }
+
+    /// Parses a sequence of elements. If `in_braces` is set the parsing stops
+    /// at the first unescaped `}`, which is not consumed.
+    fn parse_any(&mut self, in_braces: bool) -> Vec<Element> {
+        let mut elements = Vec::new();
+        let mut text = String::new();
+
+        while let Some(char) = self.peek() {
+            match char {
+                '}' if in_braces => break,
+
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek() {
+                        Some(c @ ('$' | '}' | '\\')) => {
+                            self.pos += 1;
+                            text.push(c);
+                        },
+                        _ => text.push('\\'),
+                    }
+                },
+
+                '$' => match self.parse_dollar() {
+                    Some(element) => {
+                        if !text.is_empty() {
+                            elements.push(Element::Text(std::mem::take(
+                                &mut text,
+                            )));
+                        }
+                        elements.push(element);
+                    },
+
+                    None => {
+                        self.pos += 1;
+                        text.push('$');
+                    },
+                },
+
+                _ => {
+                    self.pos += 1;
+                    text.push(char);
+                },
+            }
+        }
+
+        if !text.is_empty() {
+            elements.push(Element::Text(text));
+        }
+
+        elements
+    }
+
+    /// Tries to parse an element starting with a `$`, restoring the position
+    /// of the parser if it fails.
+    fn parse_dollar(&mut self) -> Option<Element> {
+        let start = self.pos;
+        let element = self.try_parse_dollar();
+        if element.is_none() {
+            self.pos = start;
+        }
+        element
+    }
+
+    fn try_parse_dollar(&mut self) -> Option<Element> {
+        debug_assert_eq!(Some('$'), self.peek());
+        self.pos += 1;
+
+        if !self.eat('{') {
+            if let Some(index) = self.parse_int() {
+                return Some(Element::Tabstop { index, placeholder: vec![] });
+            }
+
+            let name = self.parse_var()?;
+            return Some(Element::Variable { name, default: vec![] });
+        }
+
+        if let Some(index) = self.parse_int() {
+            if self.eat('}') {
+                return Some(Element::Tabstop { index, placeholder: vec![] });
+            }
+
+            if self.eat(':') {
+                let placeholder = self.parse_any(true);
+                return self
+                    .eat('}')
+                    .then_some(Element::Tabstop { index, placeholder });
+            }
+
+            if self.eat('|') {
+                let options = self.parse_choice_options()?;
+                return Some(Element::Choice { index, options });
+            }
+
+            return None;
+        }
+
+        let name = self.parse_var()?;
+
+        if self.eat('}') {
+            return Some(Element::Variable { name, default: vec![] });
+        }
+
+        if self.eat(':') {
+            let default = self.parse_any(true);
+            return self
+                .eat('}')
+                .then_some(Element::Variable { name, default });
+        }
+
+        if self.eat('/') {
+            self.skip_transform()?;
+            return Some(Element::Variable { name, default: vec![] });
+        }
+
+        None
+    }
+
+    fn parse_int(&mut self) -> Option<u32> {
+        let start = self.pos;
+
+        while self.peek().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+            self.pos += 1;
+        }
+
+        self.chars[start..self.pos].iter().collect::<String>().parse().ok()
+    }
+
+    fn parse_var(&mut self) -> Option<String> {
+        let start = self.pos;
+
+        match self.peek() {
+            Some(c) if c == '_' || c.is_ascii_alphabetic() => self.pos += 1,
+            _ => return None,
+        }
+
+        while self
+            .peek()
+            .map(|c| c == '_' || c.is_ascii_alphanumeric())
+            .unwrap_or(false)
+        {
+            self.pos += 1;
+        }
+
+        Some(self.chars[start..self.pos].iter().collect())
+    }
+
+    /// Parses the options of a choice after the opening `${int|`, consuming
+    /// the closing `|}`.
+    fn parse_choice_options(&mut self) -> Option<Vec<String>> {
+        let mut options = Vec::new();
+        let mut option = String::new();
+
+        loop {
+            match self.peek()? {
+                '\\' => {
+                    self.pos += 1;
+                    match self.peek()? {
+                        c @ ('$' | '}' | '\\' | ',' | '|') => {
+                            self.pos += 1;
+                            option.push(c);
+                        },
+                        _ => option.push('\\'),
+                    }
+                },
+
+                ',' => {
+                    self.pos += 1;
+                    options.push(std::mem::take(&mut option));
+                },
+
+                '|' => {
+                    self.pos += 1;
+                    if !self.eat('}') {
+                        return None;
+                    }
+                    options.push(option);
+                    return Some(options);
+                },
+
+                c => {
+                    self.pos += 1;
+                    option.push(c);
+                },
+            }
+        }
+    }
+
+    /// Skips a `regex/format/options}` transform after the first `/`,
+    /// consuming the closing `}`.
+    fn skip_transform(&mut self) -> Option<()> {
+        let mut slashes = 1;
+
+        loop {
+            match self.peek()? {
+                '\\' => self.pos += 2,
+
+                '/' => {
+                    self.pos += 1;
+                    slashes += 1;
+                },
+
+                '}' if slashes == 3 => {
+                    self.pos += 1;
+                    return Some(());
+                },
+
+                _ => self.pos += 1,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Element {
+        Element::Text(s.to_owned())
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(vec![text("foo")], parse("foo"));
+    }
+
+    #[test]
+    fn tabstops() {
+        assert_eq!(
+            vec![
+                text("foo("),
+                Element::Tabstop { index: 1, placeholder: vec![] },
+                text(")"),
+                Element::Tabstop { index: 0, placeholder: vec![] },
+            ],
+            parse("foo(${1})$0")
+        );
+    }
+
+    #[test]
+    fn nested_placeholders() {
+        assert_eq!(
+            vec![Element::Tabstop {
+                index: 1,
+                placeholder: vec![
+                    text("a, "),
+                    Element::Tabstop {
+                        index: 2,
+                        placeholder: vec![text("b")],
+                    },
+                ],
+            }],
+            parse("${1:a, ${2:b}}")
+        );
+    }
+
+    #[test]
+    fn choice() {
+        assert_eq!(
+            vec![Element::Choice {
+                index: 1,
+                options: vec!["a".to_owned(), "b,c".to_owned()],
+            }],
+            parse(r"${1|a,b\,c|}")
+        );
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(
+            vec![
+                Element::Variable {
+                    name: "TM_FILENAME".to_owned(),
+                    default: vec![],
+                },
+                Element::Variable {
+                    name: "FOO".to_owned(),
+                    default: vec![text("bar")],
+                },
+                Element::Variable { name: "BAZ".to_owned(), default: vec![] },
+            ],
+            parse("$TM_FILENAME${FOO:bar}${BAZ/(.*)/$1/g}")
+        );
+    }
+
+    #[test]
+    fn escapes_and_invalid_syntax() {
+        assert_eq!(vec![text("$1 } ${ $")], parse(r"\$1 \} ${ $"));
+    }
+}
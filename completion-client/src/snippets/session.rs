@@ -0,0 +1,288 @@
+use nvim::api::{
+    self,
+    opts::{GetExtmarkByIdOpts, SetExtmarkOpts},
+    Buffer,
+    Window,
+};
+use nvim_oxi as nvim;
+
+use super::Expansion;
+use crate::hlgroups;
+
+const SNIPPET_NAMESPACE: &str = "completion_snippet";
+
+/// A snippet that has been inserted in a buffer and whose tabstops can be
+/// jumped to.
+///
+/// Every occurrence of a tabstop is tracked with an extmark spanning its
+/// placeholder, so the positions stay valid while the buffer is edited.
+/// Typing in the first occurrence of a tabstop updates the other ones, see
+/// [`sync_mirrors`](Self::sync_mirrors).
+#[derive(Debug)]
+pub(crate) struct SnippetSession {
+    buffer: Buffer,
+
+    namespace_id: u32,
+
+    /// The extmark ids of every occurrence of every tabstop, in the order
+    /// they should be visited.
+    tabstops: Vec<Vec<u32>>,
+
+    /// The index into `tabstops` of the current tabstop, or `None` if we
+    /// haven't jumped to any tabstop yet.
+    current: Option<usize>,
+}
+
+impl SnippetSession {
+    /// Starts a new session for a snippet whose expanded text has been
+    /// inserted in the buffer at `start`, a `(row, col)` tuple.
+    pub(crate) fn new(
+        mut buffer: Buffer,
+        start: (usize, usize),
+        expansion: &Expansion,
+    ) -> nvim::Result<Self> {
+        let namespace_id = api::create_namespace(SNIPPET_NAMESPACE);
+
+        // There's at most a single snippet session at any given time.
+        buffer.clear_namespace(namespace_id, 0, usize::MAX)?;
+
+        let mut tabstops = Vec::with_capacity(expansion.tabstops.len());
+
+        for tabstop in &expansion.tabstops {
+            let mut extmarks = Vec::with_capacity(tabstop.ranges.len());
+
+            for range in &tabstop.ranges {
+                let (start_row, start_col) =
+                    self::point_at(&expansion.text, range.start, start);
+
+                let (end_row, end_col) =
+                    self::point_at(&expansion.text, range.end, start);
+
+                let opts = SetExtmarkOpts::builder()
+                    .end_row(end_row)
+                    .end_col(end_col)
+                    .hl_group(hlgroups::SNIPPET_TABSTOP)
+                    .right_gravity(false)
+                    .end_right_gravity(true)
+                    .build();
+
+                extmarks.push(buffer.set_extmark(
+                    namespace_id,
+                    start_row,
+                    start_col,
+                    &opts,
+                )?);
+            }
+
+            tabstops.push(extmarks);
+        }
+
+        Ok(Self { buffer, namespace_id, tabstops, current: None })
+    }
+
+    /// Returns the buffer the snippet was inserted in.
+    #[inline]
+    pub(crate) fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Returns whether the cursor is on the final tabstop, after which the
+    /// session can be stopped.
+    #[inline]
+    pub(crate) fn is_finished(&self) -> bool {
+        self.current == Some(self.tabstops.len() - 1)
+    }
+
+    /// Moves the cursor to the next (if `forward` is `true`) or previous
+    /// tabstop, returning whether there was a tabstop to jump to. If the
+    /// tabstop has a placeholder it's selected in select mode, so that typing
+    /// replaces it.
+    pub(crate) fn jump(&mut self, forward: bool) -> nvim::Result<bool> {
+        let next = match (self.current, forward) {
+            (None, true) => 0,
+            (None, false) | (Some(0), false) => return Ok(false),
+            (Some(current), true) => current + 1,
+            (Some(current), false) => current - 1,
+        };
+
+        let extmark_id = match self.tabstops.get(next) {
+            Some(extmarks) => extmarks[0],
+            None => return Ok(false),
+        };
+
+        let (start, end) = self.range_of(extmark_id)?;
+
+        Window::current().set_cursor(start.0 + 1, start.1)?;
+
+        if start != end {
+            self.select(start, end)?;
+        }
+
+        self.current = Some(next);
+
+        Ok(true)
+    }
+
+    /// Copies the text of the first occurrence of the current tabstop to the
+    /// other ones, if they differ.
+    ///
+    /// Has to be called outside of `on_bytes` since it edits the buffer.
+    pub(crate) fn sync_mirrors(&mut self) -> nvim::Result<()> {
+        let extmarks = match self.current {
+            Some(current) if self.tabstops[current].len() > 1 => {
+                self.tabstops[current].clone()
+            },
+            _ => return Ok(()),
+        };
+
+        let text = self.text_of(extmarks[0])?;
+
+        for &mirror in &extmarks[1..] {
+            if self.text_of(mirror)? == text {
+                continue;
+            }
+
+            let (start, end) = self.range_of(mirror)?;
+
+            // Join the mirror's update to the edit that caused it.
+            let _ = api::command("undojoin");
+
+            self.buffer.set_text(
+                start.0,
+                start.1,
+                end.0,
+                end.1,
+                text.split('\n'),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the `(row, col)` positions where the placeholder tracked by an
+    /// extmark starts and ends.
+    fn range_of(
+        &self,
+        extmark_id: u32,
+    ) -> nvim::Result<((usize, usize), (usize, usize))> {
+        let opts = GetExtmarkByIdOpts::builder().details(true).build();
+
+        let (row, col, infos) = self.buffer.get_extmark_by_id(
+            self.namespace_id,
+            extmark_id,
+            &opts,
+        )?;
+
+        let end = match infos {
+            Some(infos) => {
+                (infos.end_row.unwrap_or(row), infos.end_col.unwrap_or(col))
+            },
+            None => (row, col),
+        };
+
+        Ok(((row, col), end))
+    }
+
+    /// Returns the text of the placeholder tracked by an extmark.
+    fn text_of(&self, extmark_id: u32) -> nvim::Result<String> {
+        let (start, end) = self.range_of(extmark_id)?;
+
+        let lines = self
+            .buffer
+            .get_lines(start.0, end.0 + 1, false)?
+            .map(|line| line.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+
+        Ok(self::text_between(&lines, start.1, end.1))
+    }
+
+    /// Selects the text between `start` and `end` (exclusive) in select mode.
+    ///
+    /// Visual mode can only be entered by feeding keys, so the selection
+    /// happens after the mapping returns.
+    fn select(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> nvim::Result<()> {
+        // `go` moves the cursor to a 1-based byte offset into the buffer,
+        // which saves us from converting the columns to screen cells.
+        let first = self.buffer.get_offset(start.0)? + start.1 + 1;
+        let last = self.buffer.get_offset(end.0)? + end.1;
+
+        let keys = format!("\x1b{first}gov{last}go\x07");
+
+        api::feedkeys(&keys, "n", false);
+
+        Ok(())
+    }
+
+    /// Removes the extmarks tracking the tabstops.
+    pub(crate) fn stop(mut self) -> nvim::Result<()> {
+        self.buffer.clear_namespace(self.namespace_id, 0, usize::MAX)
+    }
+}
+
+/// Converts a byte offset into the expanded text of a snippet to a
+/// `(row, col)` position in the buffer, where `start` is the position the
+/// snippet was inserted at.
+fn point_at(
+    text: &str,
+    offset: usize,
+    start: (usize, usize),
+) -> (usize, usize) {
+    let before = &text[..offset];
+
+    match before.rfind('\n') {
+        Some(last_newline) => {
+            (start.0 + before.matches('\n').count(), offset - last_newline - 1)
+        },
+
+        None => (start.0, start.1 + offset),
+    }
+}
+
+/// Returns the text of `lines` from `start_col` on the first line to
+/// `end_col` (exclusive) on the last one.
+fn text_between(lines: &[String], start_col: usize, end_col: usize) -> String {
+    let last = lines.len() - 1;
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let end = if idx == last { end_col } else { line.len() };
+            let start = if idx == 0 { start_col } else { 0 };
+            line.get(start..end).unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn point_on_first_line() {
+        assert_eq!((3, 7), point_at("foo(bar)", 3, (3, 4)));
+    }
+
+    #[test]
+    fn point_on_later_line() {
+        assert_eq!((5, 2), point_at("fn() {\n  \n}", 9, (4, 4)));
+    }
+
+    #[test]
+    fn text_on_one_line() {
+        let lines = ["foo(bar)".to_owned()];
+        assert_eq!("bar", text_between(&lines, 4, 7));
+        assert_eq!("", text_between(&lines, 4, 4));
+    }
+
+    #[test]
+    fn text_across_lines() {
+        let lines = ["fn() {".to_owned(), "  x".to_owned(), "}".to_owned()];
+        assert_eq!("{\n  x\n", text_between(&lines, 5, 0));
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use nvim::api;
+use nvim_oxi as nvim;
+
+/// The context in which a snippet is expanded, used to resolve the values of
+/// its variables.
+pub(crate) struct VariableContext<'a> {
+    /// The path of the buffer the snippet is inserted in.
+    pub(crate) path: &'a Path,
+
+    /// The zero-based row of the line the snippet is inserted in.
+    pub(crate) row: usize,
+
+    /// The contents of the line before the snippet is inserted.
+    pub(crate) line: &'a str,
+
+    /// The text replaced by the snippet.
+    pub(crate) word: &'a str,
+}
+
+impl VariableContext<'_> {
+    /// Returns the value of a variable, or `None` if the variable is not
+    /// known.
+    ///
+    /// Has to be called on the Neovim thread.
+    pub(crate) fn resolve(&self, name: &str) -> Option<String> {
+        let path = self.path;
+
+        let value = match name {
+            "TM_SELECTED_TEXT" => String::new(),
+            "TM_CURRENT_LINE" => self.line.to_owned(),
+            "TM_CURRENT_WORD" => self.word.to_owned(),
+            "TM_LINE_INDEX" => self.row.to_string(),
+            "TM_LINE_NUMBER" => (self.row + 1).to_string(),
+            "TM_FILENAME" => path.file_name()?.to_string_lossy().into_owned(),
+            "TM_FILENAME_BASE" => {
+                path.file_stem()?.to_string_lossy().into_owned()
+            },
+            "TM_DIRECTORY" => path.parent()?.display().to_string(),
+            "TM_FILEPATH" => path.display().to_string(),
+
+            "RELATIVE_FILEPATH" => {
+                let cwd = std::env::current_dir().ok()?;
+                path.strip_prefix(cwd).unwrap_or(path).display().to_string()
+            },
+
+            "CLIPBOARD" => {
+                api::call_function::<_, String>("getreg", ("+",)).ok()?
+            },
+
+            "CURRENT_YEAR" => strftime("%Y")?,
+            "CURRENT_YEAR_SHORT" => strftime("%y")?,
+            "CURRENT_MONTH" => strftime("%m")?,
+            "CURRENT_MONTH_NAME" => strftime("%B")?,
+            "CURRENT_MONTH_NAME_SHORT" => strftime("%b")?,
+            "CURRENT_DATE" => strftime("%d")?,
+            "CURRENT_DAY_NAME" => strftime("%A")?,
+            "CURRENT_DAY_NAME_SHORT" => strftime("%a")?,
+            "CURRENT_HOUR" => strftime("%H")?,
+            "CURRENT_MINUTE" => strftime("%M")?,
+            "CURRENT_SECOND" => strftime("%S")?,
+            "CURRENT_SECONDS_UNIX" => strftime("%s")?,
+
+            _ => return None,
+        };
+
+        Some(value)
+    }
+}
+
+fn strftime(format: &str) -> Option<String> {
+    api::call_function::<_, String>("strftime", (format,)).ok()
+}
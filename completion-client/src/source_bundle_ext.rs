@@ -1,4 +1,4 @@
-use completion_types::{SourceBundle, SourceEnable};
+use completion_types::{SourceBundle, SourceEnable, SourceLimits};
 use nvim_oxi as nvim;
 
 use crate::Result;
@@ -11,6 +11,10 @@ pub(crate) trait SourceBundleExt {
     fn set_config(&mut self, config: nvim::Object) -> Result<()>;
 
     fn set_enable(&mut self, enable: SourceEnable);
+
+    fn set_priority(&mut self, priority: i32);
+
+    fn set_limits(&mut self, limits: SourceLimits);
 }
 
 impl SourceBundleExt for SourceBundle {
@@ -34,4 +38,14 @@ impl SourceBundleExt for SourceBundle {
     fn set_enable(&mut self, enable: SourceEnable) {
         self.enable = Some(enable);
     }
+
+    #[inline]
+    fn set_priority(&mut self, priority: i32) {
+        self.priority = priority;
+    }
+
+    #[inline]
+    fn set_limits(&mut self, limits: SourceLimits) {
+        self.limits = limits;
+    }
 }
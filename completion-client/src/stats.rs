@@ -0,0 +1,232 @@
+//! Performance statistics aggregated from the [`Clock`] of every completion
+//! request, displayed by the `:CompletionStats` command.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+use completion_types::{Clock, RequestKind, Revision, SourceId};
+
+/// The maximum number of samples kept for every measurement. Older samples
+/// are discarded so that the stats reflect the recent performance.
+const MAX_SAMPLES: usize = 1000;
+
+/// The percentiles displayed for every measurement.
+const PERCENTILES: [u8; 3] = [50, 90, 99];
+
+#[derive(Debug, Default)]
+pub(crate) struct Stats {
+    /// The timings of the completion pipeline for every kind of request.
+    requests: BTreeMap<&'static str, PipelineTimings>,
+
+    sources: BTreeMap<SourceId, SourceStats>,
+
+    /// The sources already recorded for the last revision. Every set of
+    /// results sent for a revision includes the sources of the previous ones,
+    /// so this is used to only count each source once.
+    recorded: (Revision, HashSet<SourceId>),
+}
+
+#[derive(Debug, Default)]
+struct PipelineTimings {
+    fetch: Samples,
+    lock_wait: Samples,
+    sort: Samples,
+    ui: Samples,
+}
+
+#[derive(Debug, Default)]
+struct SourceStats {
+    /// How many times the completions of the source were recomputed.
+    recomputed: u64,
+
+    /// How many times the previous completions of the source were reused.
+    cache_hits: u64,
+
+    /// How many times the source failed to compute its completions.
+    errors: u64,
+
+    /// How long the source took to recompute its completions.
+    latency: Samples,
+}
+
+/// The last [`MAX_SAMPLES`] measurements in milliseconds.
+#[derive(Debug, Default)]
+struct Samples(VecDeque<u64>);
+
+impl Samples {
+    fn push(&mut self, sample: u64) {
+        if self.0.len() == MAX_SAMPLES {
+            self.0.pop_front();
+        }
+        self.0.push_back(sample);
+    }
+
+    /// Returns the given percentile of the samples, or `None` if there are no
+    /// samples.
+    fn percentile(&self, percentile: u8) -> Option<u64> {
+        let mut sorted = self.0.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable();
+
+        let idx = (sorted.len() * percentile as usize).div_ceil(100);
+        sorted.get(idx.saturating_sub(1)).copied()
+    }
+
+    fn write_percentiles(&self, out: &mut String) {
+        for &p in &PERCENTILES {
+            let value = self.percentile(p).unwrap_or_default();
+            let _ = write!(out, "{value:>6}");
+        }
+    }
+}
+
+impl Stats {
+    /// Records the sources that contributed to a set of completion results.
+    pub(crate) fn record_sources(
+        &mut self,
+        revision: Revision,
+        clock: &Clock,
+    ) {
+        let (last_revision, recorded) = &mut self.recorded;
+
+        if *last_revision != revision {
+            *last_revision = revision;
+            recorded.clear();
+        }
+
+        for timing in clock.sources() {
+            if !recorded.insert(timing.source) {
+                continue;
+            }
+
+            let stats = self.sources.entry(timing.source).or_default();
+
+            if timing.from_cache {
+                stats.cache_hits += 1;
+            } else {
+                stats.recomputed += 1;
+                stats.latency.push(timing.elapsed().as_millis() as _);
+            }
+        }
+    }
+
+    /// Records the timings of a request whose results have been displayed.
+    pub(crate) fn record_timings(
+        &mut self,
+        kind: &RequestKind,
+        clock: &Clock,
+    ) {
+        let [fetch, _, ui] = clock.report();
+
+        let timings = self.requests.entry(self::kind_name(kind)).or_default();
+        timings.fetch.push(fetch);
+        timings.lock_wait.push(clock.lock_wait().as_millis() as _);
+        timings.sort.push(clock.sort_time().as_millis() as _);
+        timings.ui.push(ui);
+    }
+
+    pub(crate) fn record_error(&mut self, source: SourceId) {
+        self.sources.entry(source).or_default().errors += 1;
+    }
+
+    /// Formats the stats as the lines displayed by `:CompletionStats`.
+    pub(crate) fn to_lines(&self) -> Vec<String> {
+        let mut out = String::new();
+
+        let percentiles = PERCENTILES
+            .iter()
+            .map(|p| format!("{:>6}", format!("p{p}")))
+            .collect::<String>();
+
+        let _ = writeln!(out, "Requests (ms)");
+        let _ = writeln!(out);
+        let _ = writeln!(out, "{:<24}{:>8}{}", "", "count", percentiles);
+
+        for (kind, timings) in &self.requests {
+            for (stage, samples) in [
+                ("fetch", &timings.fetch),
+                ("lock wait", &timings.lock_wait),
+                ("sort", &timings.sort),
+                ("ui", &timings.ui),
+            ] {
+                let _ = write!(
+                    out,
+                    "{:<24}{:>8}",
+                    format!("{kind} {stage}"),
+                    samples.0.len()
+                );
+                samples.write_percentiles(&mut out);
+                let _ = writeln!(out);
+            }
+        }
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Sources (ms)");
+        let _ = writeln!(out);
+        let _ = writeln!(
+            out,
+            "{:<24}{:>12}{:>12}{:>8}{:>12}{}",
+            "", "recomputed", "cache hits", "errors", "hit rate", percentiles
+        );
+
+        for (source, stats) in &self.sources {
+            let total = stats.recomputed + stats.cache_hits;
+
+            let hit_rate = match total {
+                0 => "-".to_owned(),
+                _ => format!("{}%", stats.cache_hits * 100 / total),
+            };
+
+            let _ = write!(
+                out,
+                "{:<24}{:>12}{:>12}{:>8}{:>12}",
+                source,
+                stats.recomputed,
+                stats.cache_hits,
+                stats.errors,
+                hit_rate
+            );
+            stats.latency.write_percentiles(&mut out);
+            let _ = writeln!(out);
+        }
+
+        out.lines().map(ToOwned::to_owned).collect()
+    }
+}
+
+fn kind_name(kind: &RequestKind) -> &'static str {
+    match kind {
+        RequestKind::TypedCharacter(_) => "typed character",
+        RequestKind::RecomputeAll => "recompute all",
+        RequestKind::Invoked => "invoked",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles() {
+        let mut samples = Samples::default();
+
+        for sample in (1..=100).rev() {
+            samples.push(sample);
+        }
+
+        assert_eq!(Some(50), samples.percentile(50));
+        assert_eq!(Some(99), samples.percentile(99));
+        assert_eq!(Some(100), samples.percentile(100));
+    }
+
+    #[test]
+    fn old_samples_are_discarded() {
+        let mut samples = Samples::default();
+
+        for sample in 0..MAX_SAMPLES as u64 + 10 {
+            samples.push(sample);
+        }
+
+        assert_eq!(MAX_SAMPLES, samples.0.len());
+        assert_eq!(Some(10), samples.percentile(0));
+    }
+}
@@ -0,0 +1,402 @@
+//! Applying the edits of a completion item to a buffer.
+
+use std::cmp;
+use std::iter;
+use std::ops::Range;
+
+use completion_types::{CompletionItem, InsertTextFormat, Position, TextEdit};
+use nvim::api::{self, opts::SetExtmarkOpts, Buffer, Window};
+use nvim_oxi as nvim;
+
+use crate::snippets::{self, SnippetSession, VariableContext};
+
+const ACCEPT_NAMESPACE: &str = "completion_accept";
+
+/// The global variable holding the new contents of the command-line while
+/// it's being replaced.
+const CMDLINE_VAR: &str = "completion_cmdline";
+
+/// Inserts a completion item in the current buffer at the cursor position,
+/// applying its additional text edits (if any) in the same undo step, and
+/// moves the cursor at the end of the inserted text.
+///
+/// If the item is a snippet the cursor is moved to its first tabstop instead,
+/// and the returned session can be used to jump to the other ones.
+pub(crate) fn apply_completion(
+    item: &CompletionItem,
+    replace: bool,
+) -> nvim::Result<Option<SnippetSession>> {
+    let mut buffer = Buffer::current();
+    let mut window = Window::current();
+
+    let (row, col) = window.get_cursor()?;
+    let row = row - 1;
+
+    let line = buffer
+        .get_lines(row, row + 1, true)?
+        .next()
+        .unwrap()
+        .to_string_lossy()
+        .into_owned();
+
+    let (range, text) = self::main_edit(item, &line, col, replace);
+
+    let mut expansion = match item.insert_text_format {
+        InsertTextFormat::PlainText => None,
+
+        InsertTextFormat::Snippet => {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let path = buffer.get_name()?;
+
+            let context = VariableContext {
+                path: &path,
+                row,
+                line: &line,
+                word: &line[range.clone()],
+            };
+
+            Some(snippets::expand_snippet(text, indent, &context))
+        },
+    };
+
+    let main = TextEdit {
+        start: (row, range.start),
+        end: (row, range.end),
+        text: expansion.as_ref().map(|e| &*e.text).unwrap_or(text).to_owned(),
+    };
+
+    // The edits above the main one can add or remove lines, so we use an
+    // extmark to keep track of where the cursor should end up.
+    let namespace_id = api::create_namespace(ACCEPT_NAMESPACE);
+
+    let mut cursor_extmark = None;
+    let mut session = None;
+    let mut is_first = true;
+
+    self::apply_bottom_up(
+        &main,
+        &item.additional_text_edits,
+        |edit, is_main| {
+            // Every edit after the first one is joined to the same undo step.
+            // This fails if the user has just undone something, in which case
+            // the edit will simply be its own undo step.
+            if !is_first {
+                let _ = api::command("undojoin");
+            }
+            is_first = false;
+
+            let TextEdit { start, end, text } = edit;
+            buffer.set_text(
+                start.0,
+                start.1,
+                end.0,
+                end.1,
+                text.split('\n'),
+            )?;
+
+            if is_main {
+                let (row, col) = self::end_of_insertion(start, text);
+
+                cursor_extmark = Some(buffer.set_extmark(
+                    namespace_id,
+                    row,
+                    col,
+                    &SetExtmarkOpts::builder().build(),
+                )?);
+
+                session = expansion
+                    .take()
+                    .map(|exp| {
+                        SnippetSession::new(buffer.clone(), *start, &exp)
+                    })
+                    .transpose()?;
+            }
+
+            Ok(())
+        },
+    )?;
+
+    if let Some(extmark_id) = cursor_extmark {
+        let (row, col, _) = buffer.get_extmark_by_id(
+            namespace_id,
+            extmark_id,
+            &Default::default(),
+        )?;
+
+        buffer.del_extmark(namespace_id, extmark_id)?;
+
+        if session.is_none() {
+            window.set_cursor(row + 1, col)?;
+        }
+    }
+
+    if let Some(session) = &mut session {
+        session.jump(true)?;
+    }
+
+    match session {
+        Some(session) if session.is_finished() => {
+            session.stop()?;
+            Ok(None)
+        },
+
+        session => Ok(session),
+    }
+}
+
+/// Inserts a completion item in the command-line, whose contents are `line`
+/// with the cursor at `col`, returning the new contents of the command-line
+/// and the new column of the cursor.
+///
+/// The command-line can't be edited from a mapping, so it's replaced via
+/// `c_CTRL-\_e` by feeding keys to Neovim, which means the change is only
+/// visible after the mapping returns.
+pub(crate) fn apply_cmdline_completion(
+    item: &CompletionItem,
+    line: &str,
+    col: usize,
+    replace: bool,
+) -> nvim::Result<(String, usize)> {
+    let (line, col) = self::splice_cmdline(item, line, col, replace);
+
+    api::set_var(CMDLINE_VAR, line.as_str())?;
+
+    // The cursor can only be moved by calling `setcmdpos()` from the
+    // expression itself, so the new contents are the second item of a list.
+    let keys = format!("\x1ce[setcmdpos({}), g:{CMDLINE_VAR}][1]\r", col + 1);
+
+    api::feedkeys(&keys, "n", false);
+
+    Ok((line, col))
+}
+
+/// Returns the command-line obtained by inserting `item` in `line` with the
+/// cursor at `col`, together with the new column of the cursor.
+fn splice_cmdline(
+    item: &CompletionItem,
+    line: &str,
+    col: usize,
+    replace: bool,
+) -> (String, usize) {
+    let (range, text) = self::main_edit(item, line, col, replace);
+
+    let mut spliced = String::with_capacity(line.len() + text.len());
+    spliced.push_str(&line[..range.start]);
+    spliced.push_str(text);
+    spliced.push_str(&line[range.end..]);
+
+    (spliced, range.start + text.len())
+}
+
+/// Calls `apply` on the main edit of a completion item and on its additional
+/// edits, passing `true` for the main one.
+///
+/// The positions of all the edits refer to the buffer before any of them is
+/// applied, so they're applied from the bottom of the buffer to the top to
+/// ensure that applying one edit doesn't invalidate the positions of the
+/// others.
+fn apply_bottom_up(
+    main: &TextEdit,
+    additional: &[TextEdit],
+    mut apply: impl FnMut(&TextEdit, bool) -> nvim::Result<()>,
+) -> nvim::Result<()> {
+    let mut edits = additional
+        .iter()
+        .map(|edit| (edit, false))
+        .chain(iter::once((main, true)))
+        .collect::<Vec<_>>();
+
+    edits.sort_by_key(|(edit, _)| cmp::Reverse(edit.start));
+
+    edits.into_iter().try_for_each(|(edit, is_main)| apply(edit, is_main))
+}
+
+/// Returns the position right after `text` once it's been inserted at
+/// `start`.
+fn end_of_insertion(start: &(usize, usize), text: &str) -> (usize, usize) {
+    match text.rfind('\n') {
+        Some(last_newline) => (
+            start.0 + text.matches('\n').count(),
+            text.len() - last_newline - 1,
+        ),
+
+        None => (start.0, start.1 + text.len()),
+    }
+}
+
+/// Returns the byte range of `line` that's replaced when accepting `item`
+/// with the cursor at `col`, together with the text to insert in its place.
+///
+/// The ranges of the item's text edit were computed when the completion was
+/// requested, so the end of the range is shifted by the number of bytes that
+/// have been inserted since then. If the user has deleted back past the start
+/// of the range the edit starts at the cursor, and if the line has changed so
+/// much that the range doesn't fall on character boundaries anymore it's
+/// replaced by the prefix before the cursor.
+fn main_edit<'a>(
+    item: &'a CompletionItem,
+    line: &str,
+    col: usize,
+    replace: bool,
+) -> (Range<usize>, &'a str) {
+    if let Some(edit) = &item.text_edit {
+        let range = if replace { &edit.replace } else { &edit.insert };
+        let shift = col.saturating_sub(edit.insert.end);
+
+        let range = if range.start > col {
+            col..col
+        } else {
+            range.start..cmp::min(range.end + shift, line.len()).max(col)
+        };
+
+        if line.is_char_boundary(range.start)
+            && line.is_char_boundary(range.end)
+        {
+            return (range, &edit.text);
+        }
+    }
+
+    let text = match &item.text_edit {
+        Some(edit) => &edit.text,
+        None => item.insert_text(),
+    };
+
+    let position = Position::new(0, col as u32, line);
+
+    (col - position.len_prefix()..col, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use completion_types::CompletionTextEdit;
+
+    use super::*;
+
+    fn item_with_edit(
+        insert: Range<usize>,
+        replace: Range<usize>,
+    ) -> CompletionItem {
+        CompletionItem::builder()
+            .text("foobar")
+            .text_edit(CompletionTextEdit {
+                text: "foobar".to_owned(),
+                insert,
+                replace,
+            })
+            .build()
+    }
+
+    #[test]
+    fn replace_prefix() {
+        let item = CompletionItem::builder().text("foobar").build();
+        let (range, text) = main_edit(&item, "let a = fo", 10, false);
+        assert_eq!(8..10, range);
+        assert_eq!("foobar", text);
+    }
+
+    #[test]
+    fn insert_range_after_typing() {
+        // The completion was requested at `fo|o`, then `b` was typed.
+        let item = item_with_edit(0..2, 0..3);
+        let (range, _) = main_edit(&item, "fobo", 3, false);
+        assert_eq!(0..3, range);
+    }
+
+    #[test]
+    fn replace_range_after_typing() {
+        let item = item_with_edit(0..2, 0..3);
+        let (range, _) = main_edit(&item, "fobo", 3, true);
+        assert_eq!(0..4, range);
+    }
+
+    #[test]
+    fn cursor_before_edit_start() {
+        // The completion was requested at `foo|`, then `oo` was deleted.
+        let item = item_with_edit(1..3, 1..3);
+        let (range, _) = main_edit(&item, "f", 1, false);
+        assert_eq!(1..1, range);
+
+        let (range, _) = main_edit(&item, "fo", 0, true);
+        assert_eq!(0..0, range);
+    }
+
+    #[test]
+    fn edit_inside_character() {
+        let item = item_with_edit(1..1, 1..1);
+        let (range, text) = main_edit(&item, "éa", 3, false);
+        assert_eq!(0..3, range);
+        assert_eq!("foobar", text);
+    }
+
+    /// Applies an edit to `lines` the way `nvim_buf_set_text` would.
+    fn set_text(lines: &mut Vec<String>, edit: &TextEdit) {
+        let TextEdit { start, end, text } = edit;
+        let joined = format!(
+            "{}{text}{}",
+            &lines[start.0][..start.1],
+            &lines[end.0][end.1..]
+        );
+        lines.splice(start.0..=end.0, joined.split('\n').map(String::from));
+    }
+
+    #[test]
+    fn edits_applied_bottom_up() {
+        let mut lines = ["fn main() {", "    fo", "}", "", "// imports"]
+            .map(String::from)
+            .to_vec();
+
+        let main = TextEdit {
+            start: (1, 4),
+            end: (1, 6),
+            text: "for x in xs {\n        \n    }".to_owned(),
+        };
+
+        let import = TextEdit {
+            start: (4, 0),
+            end: (4, 0),
+            text: "use foo::xs;\n".to_owned(),
+        };
+
+        let same_row =
+            TextEdit { start: (1, 6), end: (1, 6), text: ";".to_owned() };
+
+        let mut applied = Vec::new();
+
+        apply_bottom_up(&main, &[same_row, import], |edit, is_main| {
+            set_text(&mut lines, edit);
+            applied.push(is_main);
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(vec![false, false, true], applied);
+
+        assert_eq!(
+            vec![
+                "fn main() {",
+                "    for x in xs {",
+                "        ",
+                "    };",
+                "}",
+                "",
+                "use foo::xs;",
+                "// imports",
+            ],
+            lines
+        );
+    }
+
+    #[test]
+    fn end_of_multiline_insertion() {
+        assert_eq!((1, 7), end_of_insertion(&(1, 4), "foo"));
+        assert_eq!((3, 5), end_of_insertion(&(1, 4), "{\n\n    }"));
+    }
+
+    #[test]
+    fn splice_in_cmdline() {
+        let item = CompletionItem::builder().text("edit").build();
+        let (line, col) = splice_cmdline(&item, "ed foo", 2, false);
+        assert_eq!("edit foo", line);
+        assert_eq!(4, col);
+    }
+}
@@ -1,14 +1,86 @@
-use nvim::api::{self, types::WindowBorder, Buffer, Window};
+use completion_types::ResolvedProperties;
+use nvim::api::{
+    self,
+    types::{WindowBorder, WindowConfig, WindowRelativeTo},
+    Buffer,
+    Window,
+};
+use nvim::{Array, Object};
 use nvim_oxi as nvim;
 use serde::Deserialize;
 
-use super::Border;
+use super::documentation::RenderedDetails;
+use super::{Border, DetailsGeometry};
+use crate::hlgroups;
+
+const DETAILS_NAMESPACE: &str = "completion_details";
+
+/// Highlights the code blocks of the details window. Every block is parsed
+/// with the Tree-sitter parser of its filetype if one is installed, falling
+/// back to regex-based syntax highlighting otherwise.
+const HIGHLIGHT_CODE_BLOCKS: &str = r#"
+(function(buf, ns, blocks)
+  vim.api.nvim_buf_clear_namespace(buf, ns, 0, -1)
+  vim.api.nvim_buf_call(buf, function() vim.cmd("syntax clear") end)
+
+  local get_query = vim.treesitter.query.get or vim.treesitter.query.get_query
+
+  for _, block in ipairs(blocks) do
+    local start_row, end_row, filetype = block[1], block[2], block[3]
+
+    local lang = filetype
+    if vim.treesitter.language.get_lang then
+      lang = vim.treesitter.language.get_lang(filetype) or filetype
+    end
+
+    local source = table.concat(
+      vim.api.nvim_buf_get_lines(buf, start_row, end_row, false), "\n")
+
+    local ok, parser = pcall(vim.treesitter.get_string_parser, source, lang)
+    local query = ok and get_query(lang, "highlights")
+
+    if query then
+      local root = parser:parse()[1]:root()
+      for id, node in query:iter_captures(root, source) do
+        local srow, scol, erow, ecol = node:range()
+        vim.api.nvim_buf_set_extmark(buf, ns, start_row + srow, scol, {
+          end_row = start_row + erow,
+          end_col = ecol,
+          hl_group = "@" .. query.captures[id] .. "." .. lang,
+          priority = 100,
+        })
+      end
+    else
+      vim.api.nvim_buf_call(buf, function()
+        local cluster = "CompletionDetailsCode_" .. filetype:gsub("%W", "_")
+        vim.b.current_syntax = nil
+        local included = pcall(vim.cmd, string.format(
+          "syntax include @%s syntax/%s.vim", cluster, filetype))
+        if included then
+          vim.cmd(string.format(
+            "syntax region %s start=/\\%%%dl/ end=/\\%%%dl$/ keepend contains=@%s",
+            cluster, start_row + 1, end_row, cluster))
+        end
+      end)
+    end
+  end
+end)(unpack(_A))
+"#;
 
 #[derive(Debug, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub(crate) struct DetailsConfig {
     #[serde(default = "default_details_border")]
     border: Border,
+
+    /// The maximum height of the details window. The rest of the details can
+    /// be scrolled.
+    #[serde(default = "twenty")]
+    max_height: u16,
+
+    /// The width at which the details are wrapped.
+    #[serde(default = "eighty")]
+    max_width: u16,
 }
 
 fn default_details_border() -> Border {
@@ -23,10 +95,22 @@ fn default_details_border() -> Border {
     }
 }
 
+fn twenty() -> u16 {
+    20
+}
+
+fn eighty() -> u16 {
+    80
+}
+
 impl Default for DetailsConfig {
     #[inline]
     fn default() -> Self {
-        Self { border: default_details_border() }
+        Self {
+            border: default_details_border(),
+            max_height: twenty(),
+            max_width: eighty(),
+        }
     }
 }
 
@@ -44,6 +128,9 @@ pub(crate) struct CompletionItemDetails {
 
     /// TODO: docs
     width: u16,
+
+    /// The id of the Neovim namespace used to highlight the code blocks.
+    namespace_id: u32,
 }
 
 impl Default for CompletionItemDetails {
@@ -55,6 +142,7 @@ impl Default for CompletionItemDetails {
             win: None,
             height: 0,
             width: 0,
+            namespace_id: api::create_namespace(DETAILS_NAMESPACE),
         }
     }
 }
@@ -70,11 +158,149 @@ impl CompletionItemDetails {
 
     /// Hides the completion details window if currently visible, does nothing
     /// otherwise.
-    pub(super) fn hide(&mut self) -> nvim::Result<()> {
+    pub(crate) fn hide(&mut self) -> nvim::Result<()> {
         if let Some(win) = self.win.take() {
             win.hide()?;
         }
 
         Ok(())
     }
+
+    /// Displays the resolved properties of a completion item in a floating
+    /// window placed on the side of the completion menu with more room.
+    ///
+    /// `menu_width` is the width of the menu including its border.
+    pub(super) fn show(
+        &mut self,
+        properties: &ResolvedProperties,
+        filetype: &str,
+        menu_win: &Window,
+        menu_width: u16,
+        drawable_rows: u16,
+        drawable_columns: u16,
+    ) -> nvim::Result<()> {
+        let rendered = RenderedDetails::new(
+            properties,
+            filetype,
+            self.config.max_width as usize,
+        );
+
+        if rendered.lines.is_empty() {
+            return self.hide();
+        }
+
+        let border = self.config.border.enable as u16;
+
+        let (menu_row, menu_col) = menu_win.get_position()?;
+
+        let geometry = match DetailsGeometry::new(
+            rendered.width().min(self.config.max_width as usize) as u16,
+            border,
+            menu_col as u16,
+            menu_width,
+            drawable_columns,
+        ) {
+            Some(geometry) => geometry,
+            None => return self.hide(),
+        };
+
+        self.width = geometry.width;
+
+        self.height = [
+            rendered.height(self.width as usize) as u16,
+            self.config.max_height,
+            drawable_rows.saturating_sub(menu_row as u16 + 2 * border),
+        ]
+        .into_iter()
+        .min()
+        .unwrap()
+        .max(1);
+
+        self.buf.set_lines(
+            0,
+            usize::MAX,
+            false,
+            rendered.lines.iter().map(|line| &**line),
+        )?;
+        self.highlight_code_blocks(&rendered)?;
+
+        let mut config = WindowConfig::builder();
+
+        config
+            .relative(WindowRelativeTo::Editor)
+            .height(self.height as _)
+            .width(self.width as _)
+            .row(menu_row as f64)
+            .col(geometry.col)
+            .focusable(false)
+            .noautocmd(true)
+            .zindex(200);
+
+        if self.config.border.enable {
+            config.border(self.config.border.style.clone());
+        }
+
+        let config = config.build();
+
+        match &mut self.win {
+            Some(win) => win.set_config(&config)?,
+
+            None => {
+                let mut win = api::open_win(&self.buf, false, &config)?;
+                win.set_option("wrap", true)?;
+                win.set_option(
+                    "winhighlight",
+                    format!("Normal:{}", hlgroups::DETAILS),
+                )?;
+                self.win = Some(win);
+            },
+        }
+
+        // Always show the details from the top.
+        self.win.as_mut().unwrap().set_cursor(1, 0)
+    }
+
+    /// Scrolls the contents of the details window by `lines`, down if
+    /// positive and up if negative. Does nothing if the window is hidden.
+    pub(crate) fn scroll(&mut self, lines: i32) -> nvim::Result<()> {
+        let win = match &self.win {
+            Some(win) => win,
+            None => return Ok(()),
+        };
+
+        // `<C-e>` and `<C-y>`.
+        let key = if lines > 0 { '\x05' } else { '\x19' };
+        let command = format!("normal! {}{}", lines.unsigned_abs(), key);
+
+        if lines != 0 {
+            win.call(move |_| api::command(&command))?;
+        }
+
+        Ok(())
+    }
+
+    fn highlight_code_blocks(
+        &mut self,
+        rendered: &RenderedDetails,
+    ) -> nvim::Result<()> {
+        let blocks = rendered
+            .code_blocks
+            .iter()
+            .map(|block| {
+                Array::from((
+                    block.rows.start as i64,
+                    block.rows.end as i64,
+                    block.filetype.clone(),
+                ))
+            })
+            .collect::<Array>();
+
+        let args = Array::from((self.buf.clone(), self.namespace_id, blocks));
+
+        api::call_function::<_, Object>(
+            "luaeval",
+            (HIGHLIGHT_CODE_BLOCKS, args),
+        )
+        .map(|_| ())
+    }
 }
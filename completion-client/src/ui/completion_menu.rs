@@ -1,8 +1,7 @@
 use std::cmp;
-use std::ops::{Bound, Range, RangeBounds, RangeInclusive};
-use std::slice::SliceIndex;
+use std::ops::{Range, RangeInclusive};
 
-use completion_types::{CompletionItem, ScoredCompletion};
+use completion_types::{CompletionItem, Position, ScoredCompletion};
 use nvim::api::{
     self,
     opts::SetExtmarkOpts,
@@ -14,12 +13,16 @@ use nvim_oxi as nvim;
 use serde::{de, Deserialize};
 
 use super::config::Border;
-use super::MenuGeometry;
+use super::{MenuGeometry, ScreenCursor};
 use crate::hlgroups;
 use crate::CompletionItemExt;
 
 const MENU_NAMESPACE: &str = "completion_menu";
 
+/// How many menu heights worth of completions are rendered ahead of the
+/// currently visible ones.
+const RENDERED_PAGES: usize = 2;
+
 #[derive(Debug)]
 pub(crate) struct CompletionMenu {
     /// The Neovim buffer used to display the rendered completion items.
@@ -80,6 +83,12 @@ pub(crate) struct CompletionMenu {
     /// The Neovim floating window used to hold the buffer, or `None` if the
     /// completion menu is currently closed.
     win: Option<Window>,
+
+    /// The position of the cursor in the command-line if the menu is
+    /// displaying completions for it, in which case the window is placed
+    /// relative to the editor since the buffer cursor isn't where the user
+    /// is typing.
+    cmdline_cursor: Option<ScreenCursor>,
 }
 
 impl Default for CompletionMenu {
@@ -95,6 +104,7 @@ impl Default for CompletionMenu {
             selected_completion: None,
             width: 0,
             win: None,
+            cmdline_cursor: None,
         }
     }
 }
@@ -113,9 +123,16 @@ impl CompletionMenu {
         self.win.is_some()
     }
 
+    #[inline]
+    pub(super) fn autoshow(&self) -> bool {
+        self.config.autoshow
+    }
+
     pub(crate) fn set_completions(
         &mut self,
         completions: Vec<ScoredCompletion>,
+        position: &Position,
+        is_cmdline: bool,
         drawable_rows: u16,
         drawable_columns: u16,
     ) -> nvim::Result<()> {
@@ -128,54 +145,102 @@ impl CompletionMenu {
 
             completions
                 .iter()
-                .map(|c| c.item.menu_display())
+                .map(|c| self::display(c, self.config.show_sources))
                 .map(|s| compute_width(&s))
                 .max()
                 .unwrap()
         };
 
         self.completions = completions;
-        self.render(0..desired_height)?;
+        self.selected_completion = None;
+
+        let to_render =
+            cmp::min(self.completions.len(), desired_height * RENDERED_PAGES);
+
+        self.render(0..to_render)?;
+
+        let text_offset = self.completions[0].item.text_offset();
+
+        let cursor = if is_cmdline {
+            ScreenCursor::cmdline(position)?
+        } else {
+            ScreenCursor::current(position.matched_prefix())?
+        };
+
+        self.cmdline_cursor = is_cmdline.then_some(cursor);
 
         let positioning = MenuGeometry::new(
             desired_height as u16,
             desired_width as u16,
+            self.config.border.enable as u16,
+            text_offset as u16,
+            &cursor,
             drawable_rows,
             drawable_columns,
         );
 
+        self.height = positioning.height;
+        self.width = positioning.width;
+
         if self.is_open() {
             self.move_window(positioning)?;
         } else {
             self.open_window(positioning)?;
         }
 
-        Ok(())
+        self.highlight_selected()?;
+        self.redraw_cmdline()
     }
 
+    /// Renders the completions in `range`, which either has to start at
+    /// `0`, in which case all the previously rendered completions are
+    /// discarded, or right after the end of the
+    /// [`rendered_range`](CompletionMenu::rendered_range).
     fn render(&mut self, range: Range<usize>) -> nvim::Result<()> {
-        let start = match range.start_bound() {
-            Bound::Unbounded => 0,
-            Bound::Excluded(&n) => n + 1,
-            Bound::Included(&n) => n,
-        };
+        assert!(range.start < range.end);
+        assert!(range.end <= self.completions.len());
 
-        let end = match range.end_bound() {
-            Bound::Unbounded => self.completions.len().saturating_sub(1),
-            Bound::Excluded(&n) => n.saturating_sub(1),
-            Bound::Included(&n) => n,
+        let replace_until = if range.start == 0 {
+            self.buf.clear_namespace(self.namespace_id, 0, usize::MAX)?;
+            self.buf.line_count()?
+        } else {
+            debug_assert_eq!(range.start, self.rendered_range.end() + 1);
+            range.start
         };
 
-        assert!(start <= end);
-        assert!(end < self.completions.len());
-
         let lines = self.completions[range.clone()]
             .iter()
-            .map(|c| c.item.menu_display());
+            .map(|c| self::display(c, self.config.show_sources))
+            .collect::<Vec<_>>();
+
+        self.buf.set_lines(
+            range.start,
+            replace_until,
+            false,
+            lines.iter().map(String::as_str),
+        )?;
+
+        for (row, (completion, line)) in
+            self.completions[range.clone()].iter().zip(&lines).enumerate()
+        {
+            let row = range.start + row;
+
+            if self.config.show_sources {
+                // The badges are the last thing in the line, followed by a
+                // single space.
+                let end = line.len() - 1;
+                let start = end - self::source_badges(completion).len();
 
-        self.buf.set_lines(start, end, false, lines)?;
+                let opts = SetExtmarkOpts::builder()
+                    .end_row(row)
+                    .end_col(end)
+                    .hl_group(hlgroups::MENU_SOURCES)
+                    .priority(100)
+                    .build();
+
+                self.buf.set_extmark(self.namespace_id, row, start, &opts)?;
+            }
 
-        for (row, completion) in self.completions[range].iter().enumerate() {
             for (byte_range, hl_group) in completion.item.highlight_ranges() {
                 let opts = SetExtmarkOpts::builder()
                     .end_row(row)
@@ -211,9 +276,26 @@ impl CompletionMenu {
             }
         }
 
+        self.rendered_range = RangeInclusive::new(0, range.end - 1);
+
         Ok(())
     }
 
+    /// Makes sure the completion at index `idx` has been rendered, rendering
+    /// another menu height worth of completions after it.
+    fn render_until(&mut self, idx: usize) -> nvim::Result<()> {
+        let rendered_end = *self.rendered_range.end();
+
+        if idx <= rendered_end {
+            return Ok(());
+        }
+
+        let end =
+            cmp::min(self.completions.len(), idx + 1 + self.height as usize);
+
+        self.render(rendered_end + 1..end)
+    }
+
     /// Opens the completion menu's floating window used to display the
     /// completion results.
     ///
@@ -222,17 +304,42 @@ impl CompletionMenu {
     fn open_window(&mut self, geometry: MenuGeometry) -> nvim::Result<()> {
         debug_assert!(!self.is_open());
 
-        let config = WindowConfig::builder()
-            .relative(WindowRelativeTo::Cursor)
+        let (relative, row, col) = self.placement(&geometry);
+
+        let mut config = WindowConfig::builder();
+
+        config
+            .relative(relative)
+            .anchor(geometry.position.anchor())
             .height(geometry.height as _)
             .width(geometry.width as _)
-            .row(geometry.row)
-            .col(geometry.col)
+            .row(row)
+            .col(col)
             .noautocmd(true)
-            .zindex(200)
-            .build();
+            .zindex(200);
+
+        if self.config.border.enable {
+            config.border(self.config.border.style.clone());
+        }
 
-        self.win = Some(api::open_win(&self.buf, false, &config)?);
+        let config = config.build();
+
+        let mut win = api::open_win(&self.buf, false, &config)?;
+
+        // The selected completion is highlighted with the window's
+        // `cursorline`, which also takes care of scrolling the window to
+        // keep it visible.
+        win.set_option(
+            "winhighlight",
+            format!(
+                "Normal:{},CursorLine:{}",
+                hlgroups::MENU,
+                hlgroups::MENU_SELECTED
+            ),
+        )?;
+        win.set_option("scrolloff", 0)?;
+
+        self.win = Some(win);
 
         Ok(())
     }
@@ -244,12 +351,15 @@ impl CompletionMenu {
     fn move_window(&mut self, geometry: MenuGeometry) -> nvim::Result<()> {
         debug_assert!(self.is_open());
 
+        let (relative, row, col) = self.placement(&geometry);
+
         let config = WindowConfig::builder()
-            .relative(WindowRelativeTo::Cursor)
+            .relative(relative)
+            .anchor(geometry.position.anchor())
             .height(geometry.height as _)
             .width(geometry.width as _)
-            .row(geometry.row)
-            .col(geometry.col)
+            .row(row)
+            .col(col)
             .build();
 
         self.win.as_mut().unwrap().set_config(&config)?;
@@ -257,10 +367,57 @@ impl CompletionMenu {
         Ok(())
     }
 
+    /// Returns what the menu's window is positioned relative to, together
+    /// with its row and column. The offsets of the geometry are relative to
+    /// the cursor, so they're made absolute when completing the command-line.
+    fn placement(
+        &self,
+        geometry: &MenuGeometry,
+    ) -> (WindowRelativeTo, i16, i16) {
+        match &self.cmdline_cursor {
+            Some(cursor) => (
+                WindowRelativeTo::Editor,
+                cursor.row as i16 + geometry.row,
+                cursor.col as i16 + geometry.col,
+            ),
+
+            None => (WindowRelativeTo::Cursor, geometry.row, geometry.col),
+        }
+    }
+
+    /// Neovim doesn't redraw the screen while the command-line is being
+    /// edited, so the changes to the menu's window have to be drawn
+    /// explicitly.
+    fn redraw_cmdline(&self) -> nvim::Result<()> {
+        if self.cmdline_cursor.is_some() {
+            api::command("redraw")?;
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn selected_completion(&self) -> Option<&CompletionItem> {
         self.selected_completion.map(|idx| &*self.completions[idx].item)
     }
 
+    /// Returns the currently selected completion together with the id of the
+    /// source that produced it.
+    pub(crate) fn selected_scored(&self) -> Option<&ScoredCompletion> {
+        self.selected_completion.map(|idx| &self.completions[idx])
+    }
+
+    /// Returns the window used to display the menu and its width including
+    /// the border, or `None` if the menu is currently closed.
+    pub(super) fn window(&self) -> Option<(&Window, u16)> {
+        let border = 2 * self.config.border.enable as u16;
+        self.win.as_ref().map(|win| (win, self.width + border))
+    }
+
+    /// Returns the index of the currently selected completion, if any.
+    pub(crate) fn selected_index(&self) -> Option<usize> {
+        self.selected_completion
+    }
+
     pub(crate) fn nth_completion(
         &self,
         idx: usize,
@@ -268,29 +425,107 @@ impl CompletionMenu {
         self.completions.get(idx).map(|c| &*c.item)
     }
 
-    pub(crate) fn select_next(&mut self) {
-        todo!()
+    /// Returns the `idx`-th completion together with the id of the source
+    /// that produced it.
+    pub(crate) fn nth_scored(&self, idx: usize) -> Option<&ScoredCompletion> {
+        self.completions.get(idx)
+    }
+
+    /// Selects the completion after the currently selected one. Selecting
+    /// past the last completion clears the selection, and selecting again
+    /// wraps around to the first one.
+    pub(crate) fn select_next(&mut self) -> nvim::Result<()> {
+        let next = match self.selected_completion {
+            None => Some(0),
+            Some(idx) if idx + 1 == self.completions.len() => None,
+            Some(idx) => Some(idx + 1),
+        };
+
+        self.select_completion(next)
+    }
+
+    /// Selects the completion before the currently selected one. Selecting
+    /// before the first completion clears the selection, and selecting again
+    /// wraps around to the last one.
+    pub(crate) fn select_prev(&mut self) -> nvim::Result<()> {
+        let prev = match self.selected_completion {
+            None => self.completions.len().checked_sub(1),
+            Some(0) => None,
+            Some(idx) => Some(idx - 1),
+        };
+
+        self.select_completion(prev)
     }
 
-    pub(crate) fn select_prev(&mut self) {
-        todo!()
+    /// Selects the completion at index `idx`, or clears the selection if
+    /// `idx` is `None`. Does nothing if the menu is closed or if `idx` is out
+    /// of bounds.
+    pub(crate) fn select_completion(
+        &mut self,
+        idx: Option<usize>,
+    ) -> nvim::Result<()> {
+        if !self.is_open()
+            || idx.map(|idx| idx >= self.completions.len()).unwrap_or(false)
+        {
+            return Ok(());
+        }
+
+        if let Some(idx) = idx {
+            self.render_until(idx)?;
+        }
+
+        self.selected_completion = idx;
+
+        self.highlight_selected()?;
+        self.redraw_cmdline()
     }
 
-    pub(crate) fn select_completion(&mut self, idx: usize) {
-        todo!()
+    /// Highlights the currently selected completion, scrolling the window if
+    /// it's not visible.
+    fn highlight_selected(&mut self) -> nvim::Result<()> {
+        let win = match &mut self.win {
+            Some(win) => win,
+            None => return Ok(()),
+        };
+
+        win.set_option("cursorline", self.selected_completion.is_some())?;
+        win.set_cursor(self.selected_completion.unwrap_or(0) + 1, 0)
     }
 
     pub(crate) fn close(&mut self) -> nvim::Result<()> {
         if let Some(win) = self.win.take() {
             win.hide()?;
             self.completions.clear();
+            self.rendered_range = RangeInclusive::new(0, 0);
             self.selected_completion = None;
+            self.redraw_cmdline()?;
         }
 
+        self.cmdline_cursor = None;
+
         Ok(())
     }
 }
 
+/// The line displayed in the menu for a completion, optionally followed by
+/// the badges of the sources that provided it.
+fn display(completion: &ScoredCompletion, show_sources: bool) -> String {
+    let mut display = completion.item.menu_display();
+
+    if show_sources {
+        display.push_str(&self::source_badges(completion));
+        display.push(' ');
+    }
+
+    display
+}
+
+/// Returns the names of the sources that provided a completion, e.g.
+/// `[lsp,buffer]`.
+fn source_badges(completion: &ScoredCompletion) -> String {
+    format!("[{}]", completion.sources().collect::<Vec<_>>().join(","))
+}
+
 /// TODO: docs
 fn width_compute_strategy<'item, I>(
     completions: I,
@@ -327,7 +562,7 @@ fn bytes_width(line: &str) -> usize {
 pub(crate) struct MenuConfig {
     /// Whether to automatically display the completion menu when completion
     /// results are available. If `false` the menu won't be shown until asked
-    /// explicitely via .. TODO.
+    /// explicitly via the `show` mapping.
     #[serde(default = "yes")]
     autoshow: bool,
 
@@ -336,6 +571,11 @@ pub(crate) struct MenuConfig {
 
     #[serde(default = "seven", deserialize_with = "deser_max_height")]
     pub max_height: u16,
+
+    /// Whether to display the names of the sources that provided each
+    /// completion at the end of its line.
+    #[serde(default)]
+    show_sources: bool,
 }
 
 impl Default for MenuConfig {
@@ -345,6 +585,7 @@ impl Default for MenuConfig {
             autoshow: yes(),
             border: default_menu_border(),
             max_height: seven(),
+            show_sources: false,
         }
     }
 }
@@ -0,0 +1,376 @@
+use std::ops::Range;
+
+use completion_types::{Documentation, ResolvedProperties};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The resolved properties of a completion item laid out as the lines
+/// displayed in the details window.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(super) struct RenderedDetails {
+    pub(super) lines: Vec<String>,
+
+    /// The code blocks contained in `lines`, which are highlighted with the
+    /// syntax of their filetype.
+    pub(super) code_blocks: Vec<CodeBlock>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct CodeBlock {
+    /// The rows of the block (fences excluded) in the rendered lines.
+    pub(super) rows: Range<usize>,
+
+    pub(super) filetype: String,
+}
+
+impl RenderedDetails {
+    /// Lays out the detail and the documentation of a completion item. The
+    /// detail is displayed as a header highlighted as code of the given
+    /// `filetype`, and the documentation is word-wrapped at `max_width`
+    /// columns.
+    ///
+    /// Lines inside code blocks are not wrapped since breaking them would
+    /// mess up their highlighting, so they can be longer than `max_width`.
+    pub(super) fn new(
+        properties: &ResolvedProperties,
+        filetype: &str,
+        max_width: usize,
+    ) -> Self {
+        let mut rendered = Self::default();
+
+        if let Some(detail) = &properties.detail {
+            rendered.push_code(detail.lines(), filetype);
+        }
+
+        if let Some(docs) = &properties.documentation {
+            if !rendered.lines.is_empty() {
+                rendered.lines.push(String::new());
+            }
+
+            match docs {
+                Documentation::PlainText(text) => {
+                    for line in text.lines() {
+                        rendered.push_text(line, max_width);
+                    }
+                },
+
+                Documentation::Markdown(text) => {
+                    rendered.push_markdown(text, filetype, max_width)
+                },
+            }
+        }
+
+        while rendered.lines.last().map(|l| l.is_empty()).unwrap_or(false) {
+            rendered.lines.pop();
+        }
+
+        rendered
+    }
+
+    /// Returns the number of rows needed to display the rendered lines in a
+    /// window `width` columns wide with the `wrap` option set.
+    pub(super) fn height(&self, width: usize) -> usize {
+        let width = width.max(1);
+
+        self.lines
+            .iter()
+            .map(|line| match self::width(line) {
+                0 => 1,
+                w => w.div_ceil(width),
+            })
+            .sum()
+    }
+
+    /// Returns the width of the longest rendered line.
+    pub(super) fn width(&self) -> usize {
+        self.lines.iter().map(|line| self::width(line)).max().unwrap_or(0)
+    }
+
+    fn push_code<'a, L>(&mut self, lines: L, filetype: &str)
+    where
+        L: IntoIterator<Item = &'a str>,
+    {
+        let start = self.lines.len();
+
+        self.lines.extend(lines.into_iter().map(ToOwned::to_owned));
+
+        if !filetype.is_empty() && self.lines.len() > start {
+            self.code_blocks.push(CodeBlock {
+                rows: start..self.lines.len(),
+                filetype: filetype.to_owned(),
+            });
+        }
+    }
+
+    fn push_markdown(&mut self, text: &str, filetype: &str, max_width: usize) {
+        let mut lines = text.lines();
+
+        while let Some(line) = lines.next() {
+            let fence = match self::code_fence(line) {
+                Some(fence) => fence,
+                None => {
+                    self.push_text(&self::strip_inline(line), max_width);
+                    continue;
+                },
+            };
+
+            // Code blocks without a language are assumed to be written in
+            // the same language as the buffer.
+            let lang = match line.trim_start()[fence.len()..].trim() {
+                "" => filetype,
+                lang => lang.split_whitespace().next().unwrap(),
+            };
+
+            let code = lines
+                .by_ref()
+                .take_while(|line| !line.trim_start().starts_with(fence))
+                .collect::<Vec<_>>();
+
+            self.push_code(code, lang);
+        }
+    }
+
+    /// Pushes a line of text, word-wrapping it at `max_width` columns.
+    fn push_text(&mut self, line: &str, max_width: usize) {
+        let mut wrapped = String::new();
+        let mut wrapped_width = 0;
+
+        for word in line.split_inclusive(' ') {
+            let word_width = self::width(word.trim_end());
+
+            if wrapped_width + word_width > max_width && wrapped_width > 0 {
+                self.lines.push(wrapped.trim_end().to_owned());
+                wrapped.clear();
+                wrapped_width = 0;
+            }
+
+            wrapped.push_str(word);
+            wrapped_width += self::width(word);
+        }
+
+        self.lines.push(wrapped.trim_end().to_owned());
+    }
+}
+
+/// Returns the fence opening a Markdown code block, if the line starts one.
+fn code_fence(line: &str) -> Option<&'static str> {
+    let line = line.trim_start();
+
+    if line.starts_with("```") {
+        Some("```")
+    } else if line.starts_with("~~~") {
+        Some("~~~")
+    } else {
+        None
+    }
+}
+
+/// Strips the inline Markdown syntax from a line of text outside of a code
+/// block, i.e. backslash escapes, code spans, emphasis markers and links,
+/// keeping the text of the link.
+fn strip_inline(line: &str) -> String {
+    let chars = line.chars().collect::<Vec<_>>();
+    let mut stripped = String::with_capacity(line.len());
+    let mut idx = 0;
+
+    while idx < chars.len() {
+        let ch = chars[idx];
+
+        match ch {
+            '\\' if chars
+                .get(idx + 1)
+                .is_some_and(|ch| ch.is_ascii_punctuation()) =>
+            {
+                stripped.push(chars[idx + 1]);
+                idx += 2;
+            },
+
+            '`' => {
+                let ticks = self::run_len(&chars, idx);
+                let code_start = idx + ticks;
+
+                match self::closing_ticks(&chars, code_start, ticks) {
+                    Some(code_end) => {
+                        stripped.extend(&chars[code_start..code_end]);
+                        idx = code_end + ticks;
+                    },
+                    None => {
+                        stripped.extend(&chars[idx..code_start]);
+                        idx = code_start;
+                    },
+                }
+            },
+
+            '*' | '_' | '~' => {
+                let run = self::run_len(&chars, idx);
+                let prev = idx.checked_sub(1).map(|prev| chars[prev]);
+                let next = chars.get(idx + run).copied();
+
+                let is_space =
+                    |ch: Option<char>| ch.map_or(true, char::is_whitespace);
+                let is_word =
+                    |ch: Option<char>| ch.is_some_and(char::is_alphanumeric);
+
+                // A run surrounded by spaces is just text, and so are the
+                // underscores inside words, e.g. in `snake_case`.
+                let is_text = (is_space(prev) && is_space(next))
+                    || (ch == '_' && is_word(prev) && is_word(next))
+                    || (ch == '~' && run != 2);
+
+                if is_text {
+                    stripped.extend(&chars[idx..idx + run]);
+                }
+
+                idx += run;
+            },
+
+            '!' | '[' => {
+                let open = if ch == '!' { idx + 1 } else { idx };
+
+                match self::link_at(&chars, open) {
+                    Some((text, end)) => {
+                        let text = chars[text].iter().collect::<String>();
+                        stripped.push_str(&self::strip_inline(&text));
+                        idx = end;
+                    },
+                    None => {
+                        stripped.push(ch);
+                        idx += 1;
+                    },
+                }
+            },
+
+            _ => {
+                stripped.push(ch);
+                idx += 1;
+            },
+        }
+    }
+
+    stripped
+}
+
+/// Returns the number of times the character at `start` is repeated.
+fn run_len(chars: &[char], start: usize) -> usize {
+    chars[start..].iter().take_while(|&&ch| ch == chars[start]).count()
+}
+
+/// Returns the index of the run of `ticks` backticks closing a code span
+/// whose contents start at `start`.
+fn closing_ticks(chars: &[char], start: usize, ticks: usize) -> Option<usize> {
+    let mut idx = start;
+
+    while idx < chars.len() {
+        match self::run_len(chars, idx) {
+            len if chars[idx] != '`' => idx += len,
+            len if len == ticks => return Some(idx),
+            len => idx += len,
+        }
+    }
+
+    None
+}
+
+/// If a link like `[text](url)` starts at `open`, returns the range of its
+/// text and the index right after it.
+fn link_at(chars: &[char], open: usize) -> Option<(Range<usize>, usize)> {
+    if chars.get(open) != Some(&'[') {
+        return None;
+    }
+
+    let close = open + chars[open..].iter().position(|&ch| ch == ']')?;
+
+    if chars.get(close + 1) != Some(&'(') {
+        return None;
+    }
+
+    let end = close + chars[close..].iter().position(|&ch| ch == ')')?;
+
+    Some((open + 1..close, end + 1))
+}
+
+#[inline]
+fn width(s: &str) -> usize {
+    s.graphemes(true).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn markdown(docs: &str) -> ResolvedProperties {
+        ResolvedProperties {
+            detail: Some("fn foo(bar: usize)".to_owned()),
+            documentation: Some(Documentation::Markdown(docs.to_owned())),
+        }
+    }
+
+    #[test]
+    fn detail_header_and_code_blocks() {
+        let properties = markdown("Does foo.\n\n```lua\nfoo(1)\n```\nDone.");
+        let rendered = RenderedDetails::new(&properties, "rust", 80);
+
+        assert_eq!(
+            vec!["fn foo(bar: usize)", "", "Does foo.", "", "foo(1)", "Done."],
+            rendered.lines
+        );
+
+        assert_eq!(
+            vec![
+                CodeBlock { rows: 0..1, filetype: "rust".to_owned() },
+                CodeBlock { rows: 4..5, filetype: "lua".to_owned() },
+            ],
+            rendered.code_blocks
+        );
+    }
+
+    #[test]
+    fn inline_markdown() {
+        let properties = ResolvedProperties {
+            detail: None,
+            documentation: Some(Documentation::Markdown(
+                "Returns **the** `Some\\_value` of [*foo*](https://foo.rs), \
+                 \\_not\\_ \na snake_case `a`` `` b` * c \
+                 ~~old~~\n```\n\\_kept *as is*\n```"
+                    .to_owned(),
+            )),
+        };
+
+        let rendered = RenderedDetails::new(&properties, "rust", 80);
+
+        assert_eq!(
+            vec![
+                "Returns the Some\\_value of foo, _not_",
+                "a snake_case a`` `` b * c old",
+                "\\_kept *as is*",
+            ],
+            rendered.lines
+        );
+    }
+
+    #[test]
+    fn word_wrap() {
+        let properties = ResolvedProperties {
+            detail: None,
+            documentation: Some(Documentation::PlainText(
+                "the sleazy dog jumps over the lazy fox".to_owned(),
+            )),
+        };
+
+        let rendered = RenderedDetails::new(&properties, "", 15);
+
+        assert_eq!(
+            vec!["the sleazy dog", "jumps over the", "lazy fox"],
+            rendered.lines
+        );
+    }
+
+    #[test]
+    fn wrapped_height() {
+        let rendered = RenderedDetails {
+            lines: vec!["a".repeat(25), String::new(), "b".repeat(10)],
+            code_blocks: vec![],
+        };
+
+        assert_eq!(5, rendered.height(10));
+    }
+}
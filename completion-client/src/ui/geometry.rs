@@ -1,3 +1,9 @@
+use std::cmp;
+
+use completion_types::Position;
+use nvim::api::{self, types::WindowAnchor};
+use nvim_oxi as nvim;
+
 /// The possible ways the completion menu could be placed on the screen
 /// relative to the current cursor position.
 ///
@@ -14,7 +20,10 @@
 ///          +------+------+
 ///          +------+------+
 /// ```
-#[derive(Default)]
+///
+/// The menu is placed to the east of the cursor unless it would overflow
+/// the right edge of the screen, in which case it's shifted to the west.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub(super) enum MenuPosition {
     NorthWest,
     NorthEast,
@@ -23,47 +32,334 @@ pub(super) enum MenuPosition {
     SouthEast,
 }
 
+impl MenuPosition {
+    #[inline]
+    pub(super) fn is_north(&self) -> bool {
+        matches!(self, Self::NorthWest | Self::NorthEast)
+    }
+
+    /// The corner of the window placed at the position given by
+    /// [`MenuGeometry::row`] and [`MenuGeometry::col`].
+    #[inline]
+    pub(super) fn anchor(&self) -> WindowAnchor {
+        if self.is_north() {
+            WindowAnchor::SouthWest
+        } else {
+            WindowAnchor::NorthWest
+        }
+    }
+}
+
+/// The position of the cursor on the screen.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct ScreenCursor {
+    /// The zero-based screen row of the cursor.
+    pub(super) row: u16,
+
+    /// The zero-based screen column of the cursor.
+    pub(super) col: u16,
+
+    /// The width in terminal cells of the completion prefix before the
+    /// cursor.
+    pub(super) prefix_width: u16,
+}
+
+impl ScreenCursor {
+    /// Returns the screen position of the cursor in the current window.
+    pub(super) fn current(prefix: &str) -> nvim::Result<Self> {
+        // `winline()` and `wincol()` make sure the cursor position is up to
+        // date, which isn't the case for `screenrow()` and `screencol()`.
+        let (win_row, win_col) =
+            api::call_function::<_, (u16, u16)>("win_screenpos", (0,))?;
+
+        let line = api::call_function::<_, u16>("winline", ())?;
+        let col = api::call_function::<_, u16>("wincol", ())?;
+
+        let prefix_width =
+            api::call_function::<_, u16>("strwidth", (prefix,))?;
+
+        Ok(Self {
+            row: win_row + line - 2,
+            col: win_col + col - 2,
+            prefix_width,
+        })
+    }
+
+    /// Returns the screen position of the cursor in the command-line, whose
+    /// text and cursor column are the ones of `position`.
+    pub(super) fn cmdline(position: &Position) -> nvim::Result<Self> {
+        let lines = api::get_option::<u16>("lines")?;
+
+        // The command-line takes at least a row while it's being edited, even
+        // when `cmdheight` is zero.
+        let cmdheight = cmp::max(1, api::get_option::<u16>("cmdheight")?);
+
+        let before_cursor = &position.line[..position.col];
+
+        let col = api::call_function::<_, u16>("strwidth", (before_cursor,))?;
+
+        let prefix_width = api::call_function::<_, u16>(
+            "strwidth",
+            (position.matched_prefix(),),
+        )?;
+
+        // The first cell of the command-line is taken by the `:`, `/` or `?`
+        // typed to enter it.
+        Ok(Self { row: lines - cmdheight, col: col + 1, prefix_width })
+    }
+}
+
 /// Used to tell [`CompletionMenu::open_window`] and
-/// [`CompletionMenu::close_window] where to position the completion menu's
+/// [`CompletionMenu::move_window`] where to position the completion menu's
 /// window in the buffer.
+#[derive(Debug, PartialEq, Eq)]
 pub(super) struct MenuGeometry {
-    /// The height of the window.
+    /// The height of the window, not counting the border.
     pub(super) height: u16,
 
-    /// The width of the window.
+    /// The width of the window, not counting the border.
     pub(super) width: u16,
 
-    /// Vertical offset between the line the cursor is on and the top of the
-    /// completion menu.
+    /// Vertical offset between the line the cursor is on and the
+    /// [`anchor`](MenuPosition::anchor) of the completion menu.
     ///
     /// For example:
     ///
-    /// * `0` => the first row of the menu will be at the same height as the cursor,
-    /// * `1` => the first row of the menu will be below the cursor,
-    /// * `-height - 1` => the last row of the menu will be above the cursor.
+    /// * `1` => the top of the menu will be right below the cursor (when
+    ///   placed to the south),
+    /// * `0` => the bottom of the menu will be right above the cursor (when
+    ///   placed to the north).
     pub(super) row: i16,
 
-    /// Horizontal offset between the column the cursor is on and the right
-    /// side of the completion menu.
+    /// Horizontal offset between the column the cursor is on and the left
+    /// side of the completion menu, including its border.
     ///
     /// For example:
     ///
-    /// * `0` => the first column of the menu will be aligned with the cursor,
-    /// * `-width - 1` => the last column of the menu will be aligned with the cursor.
+    /// * `0` => the first column of the menu will be aligned with the
+    ///   cursor,
+    /// * `-3` => the first column of the menu will be 3 cells to the left
+    ///   of the cursor.
     pub(super) col: i16,
+
+    /// Where the menu is placed relative to the cursor.
+    pub(super) position: MenuPosition,
 }
 
 impl MenuGeometry {
+    /// Computes where to place a menu of the desired size.
+    ///
+    /// `border` is the thickness of the border on each side of the menu,
+    /// and `text_offset` is the number of cells between the left edge of the
+    /// menu and the start of the completion text, which is aligned with the
+    /// start of the completion prefix if possible.
     pub(super) fn new(
         desired_height: u16,
         desired_width: u16,
-        // desired_position: MenuPosition,
+        border: u16,
+        text_offset: u16,
+        cursor: &ScreenCursor,
         drawable_rows: u16,
         drawable_columns: u16,
     ) -> Self {
-        Self { height: desired_height, width: desired_width, row: 1, col: 0 }
+        let borders = 2 * border;
+
+        let rows_below = drawable_rows.saturating_sub(cursor.row + 1);
+        let rows_above = cursor.row;
+
+        // Place the menu below the cursor if it fits, or if there's at least
+        // as much space below as there is above.
+        let is_north =
+            desired_height + borders > rows_below && rows_above > rows_below;
+
+        let available_rows = if is_north { rows_above } else { rows_below };
+
+        let height = cmp::max(
+            1,
+            cmp::min(desired_height, available_rows.saturating_sub(borders)),
+        );
+
+        let width = cmp::max(
+            1,
+            cmp::min(desired_width, drawable_columns.saturating_sub(borders)),
+        );
+
+        let aligned_left = cursor
+            .col
+            .saturating_sub(cursor.prefix_width + text_offset + border);
+
+        let is_west = aligned_left + width + borders > drawable_columns;
+
+        let left = if is_west {
+            drawable_columns.saturating_sub(width + borders)
+        } else {
+            aligned_left
+        };
+
+        let position = match (is_north, is_west) {
+            (true, true) => MenuPosition::NorthWest,
+            (true, false) => MenuPosition::NorthEast,
+            (false, true) => MenuPosition::SouthWest,
+            (false, false) => MenuPosition::SouthEast,
+        };
+
+        Self {
+            height,
+            width,
+            row: if is_north { 0 } else { 1 },
+            col: left as i16 - cursor.col as i16,
+            position,
+        }
+    }
+}
+
+/// Where to place the details window, to the side of the completion menu
+/// with more room, or `None` if there's no room for it on either side.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct DetailsGeometry {
+    /// The width of the window, not counting the border.
+    pub(super) width: u16,
+
+    /// The screen column of the left side of the window, including its
+    /// border.
+    pub(super) col: u16,
+}
+
+impl DetailsGeometry {
+    /// `menu_col` and `menu_width` are the screen column of the left side of
+    /// the completion menu and its width, both including the menu's border,
+    /// while `border` is the thickness of the border of the details window.
+    pub(super) fn new(
+        desired_width: u16,
+        border: u16,
+        menu_col: u16,
+        menu_width: u16,
+        drawable_columns: u16,
+    ) -> Option<Self> {
+        let borders = 2 * border;
+
+        let menu_end = menu_col + menu_width;
+        let room_east = drawable_columns.saturating_sub(menu_end);
+        let room_west = menu_col;
+
+        let is_east =
+            desired_width + borders <= room_east || room_east >= room_west;
+
+        let room = if is_east { room_east } else { room_west };
+
+        if room <= borders {
+            return None;
+        }
+
+        let width =
+            cmp::max(1, cmp::min(desired_width, room.saturating_sub(borders)));
+
+        let col =
+            if is_east { menu_end } else { menu_col - (width + borders) };
+
+        Some(Self { width, col })
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    fn cursor(row: u16, col: u16, prefix_width: u16) -> ScreenCursor {
+        ScreenCursor { row, col, prefix_width }
+    }
+
+    #[test]
+    fn below_cursor_aligned_with_prefix() {
+        let geometry =
+            MenuGeometry::new(5, 20, 0, 1, &cursor(2, 10, 3), 40, 80);
+
+        assert_eq!(
+            MenuGeometry {
+                height: 5,
+                width: 20,
+                row: 1,
+                col: -4,
+                position: MenuPosition::SouthEast,
+            },
+            geometry
+        );
+    }
+
+    #[test]
+    fn flip_above_when_no_room_below() {
+        let geometry =
+            MenuGeometry::new(5, 20, 0, 1, &cursor(36, 10, 0), 40, 80);
+
+        assert_eq!(MenuPosition::NorthEast, geometry.position);
+        assert_eq!(0, geometry.row);
+        assert_eq!(5, geometry.height);
+    }
+
+    #[test]
+    fn stay_below_when_more_room_below() {
+        let geometry =
+            MenuGeometry::new(10, 20, 0, 1, &cursor(4, 10, 0), 12, 80);
+
+        assert_eq!(MenuPosition::SouthEast, geometry.position);
+        assert_eq!(7, geometry.height);
+    }
+
+    #[test]
+    fn shift_left_at_right_edge() {
+        let geometry =
+            MenuGeometry::new(5, 20, 0, 1, &cursor(2, 75, 2), 40, 80);
+
+        assert_eq!(MenuPosition::SouthWest, geometry.position);
+        assert_eq!(-15, geometry.col);
+    }
+
+    #[test]
+    fn borders() {
+        let geometry =
+            MenuGeometry::new(5, 20, 1, 1, &cursor(33, 10, 3), 40, 80);
+
+        // 6 rows below the cursor aren't enough to fit 5 rows plus the top
+        // and bottom borders.
+        assert_eq!(MenuPosition::NorthEast, geometry.position);
+        assert_eq!(-5, geometry.col);
+    }
+
+    #[test]
+    fn clamp_to_screen() {
+        let geometry =
+            MenuGeometry::new(50, 100, 1, 1, &cursor(0, 0, 0), 10, 30);
+
+        assert_eq!(7, geometry.height);
+        assert_eq!(28, geometry.width);
+        assert_eq!(0, geometry.col);
+    }
+
+    #[test]
+    fn details_east_of_menu() {
+        let geometry = DetailsGeometry::new(30, 1, 10, 22, 80);
+        assert_eq!(Some(DetailsGeometry { width: 30, col: 32 }), geometry);
+    }
+
+    #[test]
+    fn details_west_of_menu() {
+        let geometry = DetailsGeometry::new(30, 1, 50, 22, 80);
+        assert_eq!(Some(DetailsGeometry { width: 30, col: 18 }), geometry);
+    }
+
+    #[test]
+    fn details_shrunk_to_fit() {
+        let geometry = DetailsGeometry::new(60, 0, 40, 20, 100);
+        assert_eq!(Some(DetailsGeometry { width: 40, col: 60 }), geometry);
+    }
+
+    #[test]
+    fn details_without_room() {
+        // The menu fills the screen except for 2 columns on its left, which
+        // can't fit a window with a border on each side.
+        assert_eq!(None, DetailsGeometry::new(30, 1, 2, 22, 24));
+
+        let geometry = DetailsGeometry::new(30, 1, 3, 22, 25);
+        assert_eq!(Some(DetailsGeometry { width: 1, col: 0 }), geometry);
+    }
+}
@@ -4,6 +4,7 @@ mod completion_details;
 mod completion_hint;
 mod completion_menu;
 mod config;
+mod documentation;
 mod geometry;
 mod ui_state;
 mod utils;
@@ -1,6 +1,12 @@
 use std::sync::Arc;
 
-use completion_types::{Position, ScoredCompletion};
+use completion_types::{
+    CompletionItem,
+    Document,
+    Position,
+    ResolvedProperties,
+    ScoredCompletion,
+};
 use nvim::api::Buffer;
 use nvim_oxi as nvim;
 
@@ -23,6 +29,20 @@ pub(crate) struct UiState {
     /// about a specific function.
     pub(crate) details: CompletionItemDetails,
 
+    /// The buffer and the position the completions currently displayed in
+    /// the menu were computed for, used to update the completion hint when
+    /// the selected completion changes.
+    cursor: Option<(Buffer, Arc<Position>)>,
+
+    /// The document the completions currently displayed in the menu were
+    /// computed for, whose filetype is used to highlight the details window.
+    document: Option<Arc<Document>>,
+
+    /// Whether the user has asked to show the completions since the UI was
+    /// last hidden. Unless `menu.autoshow` is set, new completions are only
+    /// displayed when this is `true`.
+    is_show_requested: bool,
+
     /// The amount of total vertical space available for drawing our UI
     /// elements.
     ///
@@ -66,26 +86,92 @@ impl UiState {
     pub(crate) fn update_completions(
         &mut self,
         completions: Vec<ScoredCompletion>,
-        mut buffer: Buffer,
+        document: Arc<Document>,
         position: Arc<Position>,
     ) -> nvim::Result<()> {
-        self.menu.set_completions(completions, self.rows, self.columns)?;
+        if !self.menu.autoshow() && !self.is_show_requested {
+            return Ok(());
+        }
+
+        let is_cmdline = document.cmdline().is_some();
+
+        self.menu.set_completions(
+            completions,
+            &position,
+            is_cmdline,
+            self.rows,
+            self.columns,
+        )?;
+
+        // The hint is virtual text placed in the buffer, so there's nowhere
+        // to show it when completing the command-line.
+        self.cursor = (!is_cmdline).then(|| (document.buffer(), position));
+        self.document = Some(document);
+        self.update_hint()
+    }
+
+    /// Updates the completion hint to show the selected completion, or the
+    /// first one if no completion is selected.
+    pub(crate) fn update_hint(&mut self) -> nvim::Result<()> {
+        let (buffer, position) = match &mut self.cursor {
+            Some((buffer, position)) => (buffer, position),
+            None => return Ok(()),
+        };
 
         let hinted_completion = self
             .menu
             .selected_completion()
-            .unwrap_or(self.menu.nth_completion(0).unwrap());
+            .or_else(|| self.menu.nth_completion(0));
 
-        // self.hint.show(hinted_completion, &mut buffer, &position)?;
+        match hinted_completion {
+            Some(completion) => self.hint.show(completion, buffer, position),
+            None => self.hint.hide(buffer),
+        }
+    }
 
-        Ok(())
+    /// Shows the resolved properties of a completion item in the details
+    /// window, but only if that item is still the one selected in the
+    /// completion menu.
+    pub(crate) fn show_details(
+        &mut self,
+        item: &Arc<CompletionItem>,
+        properties: &ResolvedProperties,
+    ) -> nvim::Result<()> {
+        let is_selected = self
+            .menu
+            .selected_scored()
+            .map(|selected| Arc::ptr_eq(&selected.item, item))
+            .unwrap_or(false);
+
+        match (self.menu.window(), &self.document) {
+            (Some((win, width)), Some(document)) if is_selected => {
+                self.details.show(
+                    properties,
+                    document.filetype(),
+                    win,
+                    width,
+                    self.rows,
+                    self.columns,
+                )
+            },
+
+            _ => Ok(()),
+        }
     }
 
-    pub(crate) fn select_completion(&mut self) {}
+    /// Makes the next completions be displayed even if `menu.autoshow` is
+    /// disabled.
+    #[inline]
+    pub(crate) fn request_show(&mut self) {
+        self.is_show_requested = true;
+    }
 
     /// Hides the completion hint, menu and details window.
     #[inline]
     pub(crate) fn hide_all(&mut self, buf: &mut Buffer) -> nvim::Result<()> {
+        self.cursor = None;
+        self.document = None;
+        self.is_show_requested = false;
         self.hint.hide(buf)?;
         self.menu.close()?;
         self.details.hide()
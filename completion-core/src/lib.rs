@@ -6,7 +6,7 @@ mod state;
 
 pub use error::Error;
 use error::Result;
-use sorter::sort;
+use sorter::{sort, Matcher, Ranker};
 use source_bundle_ext::SourceBundleExt;
 pub use start::start;
 use state::{State, StateInner};
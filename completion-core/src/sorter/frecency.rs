@@ -0,0 +1,120 @@
+//! Keeps track of how often and how recently the completions have been
+//! accepted.
+
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+use completion_types::AcceptedCompletion;
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The completions accepted by the user, indexed by the filetype of the buffer
+/// they were accepted in and by their text.
+#[derive(Debug, Default)]
+pub(crate) struct Frecency {
+    entries: HashMap<String, HashMap<String, Entry>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    /// How many times the completion has been accepted.
+    accepted: u32,
+
+    /// The last time the completion was accepted.
+    last_accepted: SystemTime,
+}
+
+impl Frecency {
+    /// Records an accepted completion.
+    pub(crate) fn record(&mut self, completion: &AcceptedCompletion) {
+        let AcceptedCompletion { text, filetype, timestamp, .. } = completion;
+
+        self.entries
+            .entry(filetype.clone())
+            .or_default()
+            .entry(text.clone())
+            .and_modify(|entry| {
+                entry.accepted = entry.accepted.saturating_add(1);
+                entry.last_accepted = entry.last_accepted.max(*timestamp);
+            })
+            .or_insert(Entry { accepted: 1, last_accepted: *timestamp });
+    }
+
+    /// Returns the frecency of the completion with the given text in buffers
+    /// of the given filetype, normalized between 0 and 1 where 0 means it was
+    /// never accepted.
+    pub(crate) fn score(
+        &self,
+        filetype: &str,
+        text: &str,
+        now: SystemTime,
+    ) -> f32 {
+        let entry =
+            match self.entries.get(filetype).and_then(|ft| ft.get(text)) {
+                Some(entry) => entry,
+                None => return 0.0,
+            };
+
+        let age = now.duration_since(entry.last_accepted).unwrap_or_default();
+
+        // Acceptances weigh less and less as they get older.
+        let recency = match age {
+            age if age < HOUR => 1.0,
+            age if age < DAY => 0.8,
+            age if age < 7 * DAY => 0.5,
+            age if age < 30 * DAY => 0.3,
+            _ => 0.1,
+        };
+
+        let frecency = entry.accepted as f32 * recency;
+
+        frecency / (frecency + 1.0)
+    }
+}
+
+impl FromIterator<AcceptedCompletion> for Frecency {
+    fn from_iter<I: IntoIterator<Item = AcceptedCompletion>>(iter: I) -> Self {
+        let mut frecency = Self::default();
+        iter.into_iter().for_each(|completion| frecency.record(&completion));
+        frecency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepted(text: &str, filetype: &str) -> AcceptedCompletion {
+        AcceptedCompletion {
+            text: text.to_owned(),
+            source: "lipsum".to_owned(),
+            filetype: filetype.to_owned(),
+            timestamp: SystemTime::now(),
+        }
+    }
+
+    #[test]
+    fn frecency() {
+        let mut frecency = Frecency::default();
+
+        for _ in 0..3 {
+            frecency.record(&accepted("foo", "rust"));
+        }
+        frecency.record(&accepted("bar", "rust"));
+
+        let now = SystemTime::now();
+        assert!(
+            frecency.score("rust", "foo", now)
+                > frecency.score("rust", "bar", now)
+        );
+        assert_eq!(0.0, frecency.score("rust", "baz", now));
+        assert_eq!(0.0, frecency.score("lua", "foo", now));
+
+        let later = now + 60 * DAY;
+        assert!(
+            frecency.score("rust", "foo", later)
+                < frecency.score("rust", "foo", now)
+        );
+    }
+}
@@ -0,0 +1,439 @@
+//! The algorithms used to filter and score the completions against the prefix
+//! typed by the user.
+
+use std::sync::Arc;
+
+use completion_types::{CaseMatching, MatcherConfig, MatcherKind};
+use fuzzy_matcher::clangd::ClangdMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+
+pub(crate) type Score = i64;
+
+/// The score of every matched character for the non-fuzzy matchers.
+const MATCH_SCORE: Score = 16;
+
+/// How much every mistyped character lowers the score of a match.
+const TYPO_PENALTY: Score = 24;
+
+pub(crate) trait Matcher: Send + Sync {
+    /// Matches `text` against `pattern`, returning a score (the higher the
+    /// better) and the byte offsets in `text` of the matched characters, or
+    /// `None` if the text doesn't match.
+    fn matches(
+        &self,
+        text: &str,
+        pattern: &str,
+    ) -> Option<(Score, Vec<usize>)>;
+
+    /// Whether every text matching a pattern also matches all the prefixes
+    /// of that pattern, in which case the completions that didn't match a
+    /// prefix can be skipped when the user keeps typing.
+    fn is_monotonic(&self) -> bool {
+        true
+    }
+}
+
+/// Creates the matcher selected in the config.
+pub(crate) fn from_config(config: &MatcherConfig) -> Arc<dyn Matcher> {
+    let MatcherConfig { kind, case, max_typos } = config.clone();
+
+    match kind {
+        MatcherKind::Fuzzy => {
+            let matcher = match case {
+                CaseMatching::Respect => {
+                    ClangdMatcher::default().respect_case()
+                },
+                CaseMatching::Ignore => ClangdMatcher::default().ignore_case(),
+                CaseMatching::Smart => ClangdMatcher::default().smart_case(),
+            };
+            Arc::new(Fuzzy { matcher, case, max_typos })
+        },
+
+        MatcherKind::FzfV2 => {
+            let matcher = match case {
+                CaseMatching::Respect => {
+                    SkimMatcherV2::default().respect_case()
+                },
+                CaseMatching::Ignore => SkimMatcherV2::default().ignore_case(),
+                CaseMatching::Smart => SkimMatcherV2::default().smart_case(),
+            };
+            Arc::new(Fuzzy { matcher, case, max_typos })
+        },
+
+        MatcherKind::Prefix => Arc::new(Prefix { case, max_typos }),
+
+        MatcherKind::Substring => Arc::new(Substring { case, max_typos }),
+
+        MatcherKind::Exact => Arc::new(Exact { case, max_typos }),
+    }
+}
+
+/// Adapts a [`FuzzyMatcher`]. A mistyped character of the pattern can stand
+/// for any character of the text, as long as the other ones still match in
+/// order.
+struct Fuzzy<M> {
+    matcher: M,
+    case: CaseMatching,
+    max_typos: u8,
+}
+
+impl<M: FuzzyMatcher> Matcher for Fuzzy<M> {
+    fn matches(
+        &self,
+        text: &str,
+        pattern: &str,
+    ) -> Option<(Score, Vec<usize>)> {
+        if let Some((score, indices)) =
+            self.matcher.fuzzy_indices(text, pattern)
+        {
+            return Some((score, self::char_to_byte_offsets(text, indices)));
+        }
+
+        let max_typos = self::allowed_typos(self.max_typos, pattern);
+
+        if max_typos == 0 {
+            return None;
+        }
+
+        let ignore_case = self::ignores_case(self.case, pattern);
+
+        let (typos, matched) =
+            self::fewest_substitutions(text, pattern, ignore_case)?;
+
+        if typos > max_typos as usize {
+            return None;
+        }
+
+        // Score the characters that were typed correctly, which are a
+        // subsequence of the text.
+        let typed =
+            matched.iter().map(|&offset| text[offset..].chars().next());
+        let (score, _) = self
+            .matcher
+            .fuzzy_indices(text, &typed.collect::<Option<String>>()?)?;
+
+        Some((score - typos as Score * TYPO_PENALTY, matched))
+    }
+}
+
+struct Prefix {
+    case: CaseMatching,
+    max_typos: u8,
+}
+
+impl Matcher for Prefix {
+    fn matches(
+        &self,
+        text: &str,
+        pattern: &str,
+    ) -> Option<(Score, Vec<usize>)> {
+        let ignore_case = self::ignores_case(self.case, pattern);
+
+        let (typos, matched) =
+            self::match_at(text, 0, pattern, ignore_case, self.max_typos)?;
+
+        // Shorter completions are closer to what's been typed.
+        let unmatched = text.chars().count() - pattern.chars().count();
+
+        Some((self::score(matched.len(), typos) - unmatched as Score, matched))
+    }
+}
+
+struct Substring {
+    case: CaseMatching,
+    max_typos: u8,
+}
+
+impl Matcher for Substring {
+    fn matches(
+        &self,
+        text: &str,
+        pattern: &str,
+    ) -> Option<(Score, Vec<usize>)> {
+        let ignore_case = self::ignores_case(self.case, pattern);
+
+        text.char_indices()
+            .enumerate()
+            .filter_map(|(start, (offset, _))| {
+                let (typos, matched) = self::match_at(
+                    &text[offset..],
+                    offset,
+                    pattern,
+                    ignore_case,
+                    self.max_typos,
+                )?;
+
+                // Matches closer to the start of the text score higher.
+                let score = self::score(matched.len(), typos) - start as Score;
+
+                Some((score, matched))
+            })
+            .max_by_key(|(score, _)| *score)
+            .or_else(|| pattern.is_empty().then(|| (0, Vec::new())))
+    }
+}
+
+struct Exact {
+    case: CaseMatching,
+    max_typos: u8,
+}
+
+impl Matcher for Exact {
+    fn matches(
+        &self,
+        text: &str,
+        pattern: &str,
+    ) -> Option<(Score, Vec<usize>)> {
+        if text.chars().count() != pattern.chars().count() {
+            return None;
+        }
+
+        let ignore_case = self::ignores_case(self.case, pattern);
+
+        let (typos, matched) =
+            self::match_at(text, 0, pattern, ignore_case, self.max_typos)?;
+
+        Some((self::score(matched.len(), typos), matched))
+    }
+
+    fn is_monotonic(&self) -> bool {
+        false
+    }
+}
+
+/// Matches the start of `text` against `pattern` character by character,
+/// returning the number of typos and the byte offsets of the matched
+/// characters shifted by `offset`.
+fn match_at(
+    text: &str,
+    offset: usize,
+    pattern: &str,
+    ignore_case: bool,
+    max_typos: u8,
+) -> Option<(u8, Vec<usize>)> {
+    let max_typos = self::allowed_typos(max_typos, pattern);
+    let mut text_chars = text.char_indices();
+    let mut matched = Vec::new();
+    let mut typos = 0;
+
+    for pattern_char in pattern.chars() {
+        let (idx, text_char) = text_chars.next()?;
+
+        if self::chars_eq(text_char, pattern_char, ignore_case) {
+            matched.push(offset + idx);
+        } else if typos < max_typos {
+            typos += 1;
+        } else {
+            return None;
+        }
+    }
+
+    Some((typos, matched))
+}
+
+/// Finds the fewest characters of `pattern` that have to be substituted for
+/// it to be a subsequence of `text`, returning their number together with
+/// the byte offsets of the characters of the text matched by the other ones,
+/// or `None` if the pattern is longer than the text. Runs in
+/// `O(text * pattern)`.
+fn fewest_substitutions(
+    text: &str,
+    pattern: &str,
+    ignore_case: bool,
+) -> Option<(usize, Vec<usize>)> {
+    let (offsets, text): (Vec<_>, Vec<_>) = text.char_indices().unzip();
+    let pattern = pattern.chars().collect::<Vec<_>>();
+
+    if pattern.len() > text.len() {
+        return None;
+    }
+
+    // `subs[i][j]` is the fewest substitutions needed to fit the first `i`
+    // characters of the pattern in the first `j` characters of the text.
+    let mut subs = vec![vec![usize::MAX; text.len() + 1]; pattern.len() + 1];
+    subs[0].fill(0);
+
+    for i in 1..=pattern.len() {
+        for j in i..=text.len() {
+            let is_typo =
+                !self::chars_eq(text[j - 1], pattern[i - 1], ignore_case);
+            let take = subs[i - 1][j - 1].saturating_add(is_typo as usize);
+            subs[i][j] = subs[i][j - 1].min(take);
+        }
+    }
+
+    let mut matched = Vec::new();
+    let (mut i, mut j) = (pattern.len(), text.len());
+
+    while i > 0 {
+        if j > i && subs[i][j] == subs[i][j - 1] {
+            j -= 1;
+            continue;
+        }
+        if self::chars_eq(text[j - 1], pattern[i - 1], ignore_case) {
+            matched.push(offsets[j - 1]);
+        }
+        i -= 1;
+        j -= 1;
+    }
+
+    matched.reverse();
+
+    Some((subs[pattern.len()][text.len()], matched))
+}
+
+/// Returns how many typos `pattern` can contain. At least one character has
+/// to be typed correctly, or every completion would match a pattern shorter
+/// than `max_typos`.
+#[inline]
+fn allowed_typos(max_typos: u8, pattern: &str) -> u8 {
+    let len = pattern.chars().count().saturating_sub(1);
+    max_typos.min(len.try_into().unwrap_or(u8::MAX))
+}
+
+#[inline]
+fn score(matched: usize, typos: u8) -> Score {
+    matched as Score * MATCH_SCORE - typos as Score * TYPO_PENALTY
+}
+
+#[inline]
+fn chars_eq(a: char, b: char, ignore_case: bool) -> bool {
+    a == b || (ignore_case && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+#[inline]
+fn ignores_case(case: CaseMatching, pattern: &str) -> bool {
+    match case {
+        CaseMatching::Respect => false,
+        CaseMatching::Ignore => true,
+        CaseMatching::Smart => !pattern.chars().any(char::is_uppercase),
+    }
+}
+
+/// The fuzzy matchers return the indices of the matched characters, which
+/// we convert to byte offsets.
+fn char_to_byte_offsets(text: &str, indices: Vec<usize>) -> Vec<usize> {
+    if text.is_ascii() {
+        return indices;
+    }
+
+    let offsets =
+        text.char_indices().map(|(offset, _)| offset).collect::<Vec<_>>();
+
+    indices.into_iter().filter_map(|idx| offsets.get(idx).copied()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(kind: MatcherKind, max_typos: u8) -> Arc<dyn Matcher> {
+        self::from_config(&MatcherConfig {
+            kind,
+            case: CaseMatching::Smart,
+            max_typos,
+        })
+    }
+
+    #[test]
+    fn prefix() {
+        let prefix = matcher(MatcherKind::Prefix, 0);
+        assert_eq!(
+            Some(vec![0, 1]),
+            prefix.matches("Foobar", "fo").map(|m| m.1)
+        );
+        assert_eq!(None, prefix.matches("foobar", "Fo"));
+        assert_eq!(None, prefix.matches("foobar", "ob"));
+    }
+
+    #[test]
+    fn prefix_with_typos() {
+        let prefix = matcher(MatcherKind::Prefix, 1);
+        assert_eq!(
+            Some(vec![0, 2]),
+            prefix.matches("foobar", "fxo").map(|m| m.1)
+        );
+        assert_eq!(None, prefix.matches("foobar", "fxx"));
+    }
+
+    #[test]
+    fn substring_prefers_earlier_matches() {
+        let substring = matcher(MatcherKind::Substring, 0);
+        assert_eq!(
+            Some(vec![1, 2]),
+            substring.matches("abab", "ba").map(|m| m.1)
+        );
+        assert_eq!(None, substring.matches("abab", "bb"));
+    }
+
+    #[test]
+    fn exact() {
+        let exact = matcher(MatcherKind::Exact, 0);
+        assert!(exact.matches("foo", "foo").is_some());
+        assert!(exact.matches("foobar", "foo").is_none());
+        assert!(!exact.is_monotonic());
+    }
+
+    #[test]
+    fn fuzzy_byte_offsets() {
+        let fuzzy = matcher(MatcherKind::FzfV2, 0);
+        assert_eq!(Some(vec![0, 2]), fuzzy.matches("àbc", "àb").map(|m| m.1));
+    }
+
+    #[test]
+    fn fuzzy_with_typos() {
+        let fuzzy = matcher(MatcherKind::Fuzzy, 1);
+        assert!(fuzzy.matches("foobar", "fozb").is_some());
+        assert!(matcher(MatcherKind::Fuzzy, 0)
+            .matches("foobar", "fozb")
+            .is_none());
+    }
+
+    #[test]
+    fn typos_are_substitutions() {
+        // A mistyped character still has to stand for one in the text.
+        for kind in [MatcherKind::Fuzzy, MatcherKind::FzfV2] {
+            let fuzzy = matcher(kind, 1);
+            assert_eq!(
+                Some(vec![0, 2]),
+                fuzzy.matches("foo", "fxo").map(|m| m.1)
+            );
+            assert!(fuzzy.matches("fo", "fxo").is_none());
+            assert!(fuzzy.matches("foobar", "fxxb").is_none());
+        }
+        assert!(matcher(MatcherKind::Prefix, 1)
+            .matches("fo", "fxo")
+            .is_none());
+    }
+
+    #[test]
+    fn short_patterns_need_a_correct_character() {
+        for kind in [
+            MatcherKind::Fuzzy,
+            MatcherKind::FzfV2,
+            MatcherKind::Prefix,
+            MatcherKind::Substring,
+        ] {
+            let matcher = matcher(kind, 1);
+            assert!(matcher.matches("foo", "x").is_none());
+            assert!(matcher.matches("foo", "f").is_some());
+            assert!(matcher.matches("foo", "fx").is_some());
+        }
+        assert!(matcher(MatcherKind::Exact, 2).matches("f", "x").is_none());
+        assert!(matcher(MatcherKind::Exact, 2).matches("fo", "xx").is_none());
+    }
+
+    #[test]
+    fn fewest_substitutions() {
+        assert_eq!(
+            Some((1, vec![0, 1, 3])),
+            super::fewest_substitutions("foobar", "fozb", false)
+        );
+        assert_eq!(
+            Some((0, vec![3, 4, 5])),
+            super::fewest_substitutions("foobar", "bar", false)
+        );
+        assert_eq!(None, super::fewest_substitutions("foo", "fooo", false));
+    }
+}
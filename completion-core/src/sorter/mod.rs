@@ -0,0 +1,119 @@
+mod frecency;
+mod matcher;
+mod ranking;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use completion_types::{
+    CompletionItem,
+    CompletionRequest,
+    Document,
+    ScoredCompletion,
+    SourceId,
+};
+use matcher::Score;
+pub(crate) use matcher::{from_config as matcher, Matcher};
+pub(crate) use ranking::Ranker;
+use rayon::prelude::*;
+
+/// Sorts a list of completion items against a specific request, filtering out
+/// the completions that don't match the request.
+pub(crate) fn sort(
+    items: Vec<(SourceId, Arc<CompletionItem>)>,
+    request: &CompletionRequest,
+    matcher: &dyn Matcher,
+    ranker: &Ranker,
+) -> Vec<ScoredCompletion> {
+    let prefix = request.position.matched_prefix();
+
+    let completions = items
+        .into_par_iter()
+        .filter_map(|(source, item)| {
+            let (score, matched_bytes) =
+                score_completion(matcher, &item, &request.document, &prefix)?;
+
+            Some(ScoredCompletion {
+                item,
+                source,
+                score,
+                matched_bytes,
+                duplicates: Vec::new(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let completions = ranker.dedup(completions);
+
+    ranker.rank(completions, request.document.filetype())
+}
+
+/// Keeps at most the first `max_items(source)` completions of every source,
+/// dropping the others.
+pub(crate) fn limit_items<F>(
+    completions: Vec<ScoredCompletion>,
+    max_items: F,
+) -> Vec<ScoredCompletion>
+where
+    F: Fn(SourceId) -> Option<usize>,
+{
+    let mut shown = HashMap::<SourceId, usize>::new();
+
+    completions
+        .into_iter()
+        .filter(|completion| {
+            let count = shown.entry(completion.source).or_default();
+            *count += 1;
+            match max_items(completion.source) {
+                Some(max) => *count <= max,
+                None => true,
+            }
+        })
+        .collect()
+}
+
+/// Scores a single completion item against a prefix, which is usually the word
+/// under the cursor (delimited by some word-boundary rules).
+///
+/// If the completion doesn't match the prefix a `None` value will be returned.
+///
+/// If it matches it also returns a vector containing the characters of the
+/// completion item that are matched by the prefix.
+fn score_completion(
+    matcher: &dyn Matcher,
+    completion: &CompletionItem,
+    _document: &Document,
+    prefix: &str,
+) -> Option<(Score, Vec<usize>)> {
+    matcher.matches(completion.filter_text(), prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_items_per_source() {
+        let completion = |source| ScoredCompletion {
+            item: Arc::new(CompletionItem::builder().text("foo").build()),
+            source,
+            score: 0,
+            matched_bytes: Vec::new(),
+            duplicates: Vec::new(),
+        };
+
+        let completions = ["lsp", "lipsum", "lipsum", "lsp", "lipsum"]
+            .into_iter()
+            .map(completion)
+            .collect();
+
+        let limited = limit_items(completions, |source| {
+            (source == "lipsum").then_some(2)
+        });
+
+        assert_eq!(
+            vec!["lsp", "lipsum", "lipsum", "lsp"],
+            limited.iter().map(|c| c.source).collect::<Vec<_>>()
+        );
+    }
+}
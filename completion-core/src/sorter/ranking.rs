@@ -0,0 +1,345 @@
+//! Ranks the completions matching a request by mixing their match score with
+//! the other signals configured in the [`RankingWeights`].
+
+use std::collections::HashMap;
+use std::sync::{PoisonError, RwLock};
+use std::time::SystemTime;
+
+use completion_types::{
+    AcceptedCompletion,
+    RankingWeights,
+    ScoredCompletion,
+    SourceBundle,
+    SourceId,
+};
+use rayon::prelude::*;
+
+use super::frecency::Frecency;
+use super::matcher::Score;
+
+pub(crate) struct Ranker {
+    weights: RankingWeights,
+
+    /// Whether to merge the completions with the same insert text.
+    dedup: bool,
+
+    /// The priorities of the enabled sources.
+    priorities: HashMap<SourceId, i32>,
+
+    /// The completions accepted by the user.
+    frecency: RwLock<Frecency>,
+}
+
+impl Ranker {
+    /// Creates a new ranker, using the completions accepted in the previous
+    /// sessions to compute their frecencies.
+    pub(crate) fn new(
+        weights: RankingWeights,
+        dedup: bool,
+        sources: &[SourceBundle],
+        history: Vec<AcceptedCompletion>,
+    ) -> Self {
+        let priorities = sources
+            .iter()
+            .map(|source| (source.id, source.priority))
+            .collect();
+
+        let frecency = RwLock::new(history.into_iter().collect());
+
+        Self { weights, dedup, priorities, frecency }
+    }
+
+    /// Records an accepted completion.
+    pub(crate) fn record_accepted(&self, completion: &AcceptedCompletion) {
+        // The frecencies are only used for ranking, so it's fine to keep
+        // using them even if a thread panicked while updating them.
+        self.frecency
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .record(completion);
+    }
+
+    #[inline]
+    fn priority(&self, source: SourceId) -> f32 {
+        self.priorities.get(source).copied().unwrap_or_default() as f32
+    }
+
+    /// Merges the completions with the same insert text if deduplication is
+    /// enabled, keeping the one from the source with the highest priority, or
+    /// the best matching one if the priorities are the same.
+    pub(crate) fn dedup(
+        &self,
+        mut completions: Vec<ScoredCompletion>,
+    ) -> Vec<ScoredCompletion> {
+        if !self.dedup {
+            return completions;
+        }
+
+        completions.par_sort_by(|a, b| {
+            a.item
+                .insert_text()
+                .cmp(b.item.insert_text())
+                .then_with(|| {
+                    self.priority(b.source).total_cmp(&self.priority(a.source))
+                })
+                .then_with(|| b.score.cmp(&a.score))
+        });
+
+        let mut deduped = Vec::<ScoredCompletion>::new();
+
+        for completion in completions {
+            match deduped.last_mut() {
+                Some(kept)
+                    if kept.item.insert_text()
+                        == completion.item.insert_text() =>
+                {
+                    kept.duplicates.push((completion.source, completion.item));
+                },
+
+                _ => deduped.push(completion),
+            }
+        }
+
+        deduped
+    }
+
+    /// Sorts the completions requested by a buffer of the given filetype from
+    /// the highest to the lowest ranked.
+    pub(crate) fn rank(
+        &self,
+        completions: Vec<ScoredCompletion>,
+        filetype: &str,
+    ) -> Vec<ScoredCompletion> {
+        let RankingWeights {
+            score,
+            source_priority,
+            sort_text,
+            preselect,
+            locality,
+            frecency,
+        } = self.weights;
+
+        // Match scores are relative to the best one, so that completions
+        // matching almost as well as the best one aren't penalized too much.
+        let best_score =
+            completions.iter().map(|c| c.score).max().unwrap_or_default();
+
+        let priorities =
+            Range::new(completions.iter().map(|c| self.priority(c.source)));
+
+        let sort_text_positions = self::sort_text_positions(&completions);
+
+        let frecencies =
+            &*self.frecency.read().unwrap_or_else(PoisonError::into_inner);
+
+        let now = SystemTime::now();
+
+        let mut ranked = completions
+            .into_par_iter()
+            .zip(sort_text_positions)
+            .map(|(completion, sort_text_position)| {
+                let item = &*completion.item;
+
+                let rank = score
+                    * self::relative_score(completion.score, best_score)
+                    + source_priority
+                        * priorities
+                            .normalize(self.priority(completion.source))
+                    + sort_text * sort_text_position
+                    + preselect * (item.is_preselected as u8 as f32)
+                    + locality
+                        * item
+                            .distance
+                            .map_or(0.0, |d| 1.0 / (d as f32 + 1.0))
+                    + frecency * frecencies.score(filetype, &item.text, now);
+
+                (rank, completion)
+            })
+            .collect::<Vec<_>>();
+
+        ranked.par_sort_by(|(rank_a, a), (rank_b, b)| {
+            rank_b
+                .total_cmp(rank_a)
+                .then_with(|| a.item.sort_text().cmp(b.item.sort_text()))
+        });
+
+        ranked.into_iter().map(|(_, completion)| completion).collect()
+    }
+}
+
+/// Maps a match score between 0 and 1 relative to the best one.
+#[inline]
+fn relative_score(score: Score, best: Score) -> f32 {
+    if best > 0 {
+        score.max(0) as f32 / best as f32
+    } else {
+        0.0
+    }
+}
+
+/// The range of the values taken by one of the ranking components.
+struct Range {
+    min: f32,
+    max: f32,
+}
+
+impl Range {
+    fn new<I: Iterator<Item = f32>>(values: I) -> Self {
+        values.fold(Self { min: f32::MAX, max: f32::MIN }, |range, value| {
+            Self { min: range.min.min(value), max: range.max.max(value) }
+        })
+    }
+
+    /// Maps a value between 0 and 1. Components taking the same value for
+    /// every completion don't affect the ranking, so they're mapped to 0.
+    #[inline]
+    fn normalize(&self, value: f32) -> f32 {
+        if self.max > self.min {
+            (value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Returns the position of every completion among the ones of the same source
+/// when sorted by their sort text, mapped between 1 for the first one and 0 for
+/// the last one. The only completion of a source is mapped to 1.
+///
+/// The sort texts of different sources aren't comparable, so each source is
+/// ranked on its own.
+fn sort_text_positions(completions: &[ScoredCompletion]) -> Vec<f32> {
+    let mut indices = (0..completions.len()).collect::<Vec<_>>();
+
+    indices.par_sort_by(|&a, &b| {
+        let (a, b) = (&completions[a], &completions[b]);
+        a.source
+            .cmp(b.source)
+            .then_with(|| a.item.sort_text().cmp(b.item.sort_text()))
+    });
+
+    let mut positions = vec![0.0; completions.len()];
+
+    let mut start = 0;
+
+    while start < indices.len() {
+        let source = completions[indices[start]].source;
+
+        let len = indices[start..]
+            .iter()
+            .take_while(|&&idx| completions[idx].source == source)
+            .count();
+
+        let last = len.saturating_sub(1).max(1) as f32;
+
+        for (pos, &idx) in indices[start..start + len].iter().enumerate() {
+            positions[idx] = 1.0 - pos as f32 / last;
+        }
+
+        start += len;
+    }
+
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use completion_types::CompletionItem;
+
+    use super::*;
+
+    fn completion(
+        source: SourceId,
+        text: &str,
+        score: Score,
+    ) -> ScoredCompletion {
+        ScoredCompletion {
+            item: Arc::new(CompletionItem::builder().text(text).build()),
+            source,
+            score,
+            matched_bytes: Vec::new(),
+            duplicates: Vec::new(),
+        }
+    }
+
+    fn ranker(priorities: &[(SourceId, i32)]) -> Ranker {
+        Ranker {
+            weights: RankingWeights::default(),
+            dedup: true,
+            priorities: priorities.iter().copied().collect(),
+            frecency: RwLock::default(),
+        }
+    }
+
+    fn texts(completions: &[ScoredCompletion]) -> Vec<&str> {
+        completions.iter().map(|c| &*c.item.text).collect()
+    }
+
+    #[test]
+    fn source_priority() {
+        let ranker = ranker(&[("lipsum", 0), ("lsp", 10)]);
+
+        let ranked = ranker.rank(
+            vec![
+                completion("lipsum", "foo", 100),
+                completion("lsp", "foobar", 90),
+            ],
+            "rust",
+        );
+
+        assert_eq!(vec!["foobar", "foo"], texts(&ranked));
+    }
+
+    #[test]
+    fn frecency() {
+        let ranker = ranker(&[]);
+        ranker.record_accepted(&AcceptedCompletion {
+            text: "baz".to_owned(),
+            source: "lipsum".to_owned(),
+            filetype: "rust".to_owned(),
+            timestamp: SystemTime::now(),
+        });
+
+        let ranked = ranker.rank(
+            vec![
+                completion("lipsum", "bar", 50),
+                completion("lipsum", "baz", 50),
+                completion("lipsum", "bay", 50),
+            ],
+            "rust",
+        );
+
+        // `baz` was accepted, the others are sorted by their text.
+        assert_eq!(vec!["baz", "bar", "bay"], texts(&ranked));
+    }
+
+    #[test]
+    fn sort_text_positions() {
+        let positions = super::sort_text_positions(&[
+            completion("lipsum", "c", 0),
+            completion("lsp", "foo", 0),
+            completion("lipsum", "a", 0),
+            completion("lipsum", "b", 0),
+        ]);
+
+        assert_eq!(vec![0.0, 1.0, 1.0, 0.5], positions);
+    }
+
+    #[test]
+    fn dedup() {
+        let ranker = ranker(&[("lipsum", 0), ("lsp", 10)]);
+
+        let deduped = ranker.dedup(vec![
+            completion("lipsum", "foo", 100),
+            completion("lsp", "foo", 90),
+            completion("lipsum", "bar", 80),
+        ]);
+
+        assert_eq!(2, deduped.len());
+
+        let foo = deduped.iter().find(|c| &*c.item.text == "foo").unwrap();
+        assert_eq!(vec!["lsp", "lipsum"], foo.sources().collect::<Vec<_>>());
+    }
+}
@@ -1,7 +1,9 @@
 use async_trait::async_trait;
 use completion_types::{
+    BufferEdit,
     CompletionItem,
     CompletionList,
+    CompletionTrigger,
     CoreSender,
     Document,
     GenericError,
@@ -29,6 +31,7 @@ pub(crate) trait SourceBundleExt {
         &self,
         document: &Document,
         position: &Position,
+        trigger: CompletionTrigger,
     ) -> Result<CompletionList, GenericError>;
 
     async fn resolve_completion(
@@ -36,6 +39,8 @@ pub(crate) trait SourceBundleExt {
         document: &Document,
         completion: &CompletionItem,
     ) -> Result<Option<ResolvedProperties>, GenericError>;
+
+    fn on_edit(&self, document: &Document, edit: &BufferEdit);
 }
 
 #[async_trait]
@@ -85,9 +90,10 @@ impl SourceBundleExt for SourceBundle {
         &self,
         document: &Document,
         position: &Position,
+        trigger: CompletionTrigger,
     ) -> Result<CompletionList, GenericError> {
         let config = self.config.as_ref().unwrap();
-        self.source.complete(document, position, config).await
+        self.source.complete(document, position, trigger, config).await
     }
 
     async fn resolve_completion(
@@ -98,4 +104,9 @@ impl SourceBundleExt for SourceBundle {
         let config = self.config.as_ref().unwrap();
         self.source.resolve_completion(document, completion, config).await
     }
+
+    fn on_edit(&self, document: &Document, edit: &BufferEdit) {
+        let config = self.config.as_ref().unwrap();
+        self.source.on_edit(document, edit, config)
+    }
 }
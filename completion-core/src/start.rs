@@ -1,18 +1,26 @@
 use std::{panic, thread};
 
 use completion_types::{
+    AcceptedCompletion,
     ClientMessage,
     ClientReceiver,
     CoreMessage,
     CoreSender,
+    MatcherConfig,
+    RankingWeights,
     SourceBundle,
 };
 
 use crate::{Result, State};
 
-/// Starts the completion core on a new thread.
+/// Starts the completion core on a new thread. The `history` contains the
+/// completions accepted in the previous sessions.
 pub fn start(
     sources: Vec<SourceBundle>,
+    matcher: MatcherConfig,
+    ranking: RankingWeights,
+    dedup: bool,
+    history: Vec<AcceptedCompletion>,
     core_sender: CoreSender,
     client_receiver: ClientReceiver,
 ) {
@@ -43,7 +51,14 @@ pub fn start(
     }));
 
     thread::spawn(move || {
-        let core = State::new(sources, core_sender.clone());
+        let core = State::new(
+            sources,
+            &matcher,
+            ranking,
+            dedup,
+            history,
+            core_sender.clone(),
+        );
 
         match self::event_loop(core, client_receiver) {
             Err(error) => {
@@ -76,9 +91,25 @@ async fn event_loop(core: State, mut receiver: ClientReceiver) -> Result<()> {
                 id,
             } => core.resolve_completion(document, item, source, id)?,
 
+            ClientMessage::CompletionAccepted { completion } => {
+                core.record_accepted(&completion)?
+            },
+
+            ClientMessage::BufferEdited { buffer, edit } => {
+                core.buffer_edited(buffer, edit)?
+            },
+
             ClientMessage::CancelRequest { revision } => {
                 core.stop_sending(revision)?
             },
+
+            ClientMessage::DetachSources { buffer, sources } => {
+                core.detach_sources(buffer, sources)?
+            },
+
+            ClientMessage::ReattachSources { buffer, sources } => {
+                core.reattach_sources(buffer, sources)?
+            },
         }
     }
 
@@ -1,26 +1,34 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use completion_types::{
+    AcceptedCompletion,
+    BufferEdit,
     Clock,
     CompletionItem,
     CompletionList,
     CompletionRequest,
+    CompletionTrigger,
     CoreMessage,
     CoreSender,
     Document,
     GenericError,
+    MatcherConfig,
+    RankingWeights,
     RequestKind,
     ResolvedProperties,
     Revision,
     ScoredCompletion,
     SourceBundle,
     SourceId,
+    SourceLimits,
+    SourceTiming,
 };
 use nvim_oxi::api::Buffer;
 use rayon::prelude::*;
 
-use crate::{Result, SourceBundleExt};
+use crate::{Matcher, Ranker, Result, SourceBundleExt};
 
 type IsComplete = bool;
 type TriggerCharacters = Vec<char>;
@@ -43,6 +51,18 @@ pub(crate) struct StateInner {
     /// Map from [`Buffer`]s to completion sources enabled for that buffer.
     buffer_sources: HashMap<Buffer, Vec<AttachedSource>>,
 
+    /// The documents of the buffers that have at least one source attached.
+    documents: HashMap<Buffer, Arc<Document>>,
+
+    /// Map from [`Buffer`]s to the completion sources that were enabled for
+    /// that buffer but have been detached by the user. They're moved back
+    /// to `buffer_sources` when re-attached.
+    detached_sources: HashMap<Buffer, Vec<AttachedSource>>,
+
+    /// The sources detached from every buffer, which are also detached from
+    /// the buffers attached later on.
+    detached_everywhere: HashSet<SourceId>,
+
     /// The last revision sent from the client.
     revision: Revision,
 
@@ -58,12 +78,60 @@ pub(crate) struct StateInner {
     /// TODO: docs
     completions:
         HashMap<SourceId, (Vec<Arc<CompletionItem>>, IsComplete, Revision)>,
+
+    /// The timings of the sources whose completions are up to date with the
+    /// last revision.
+    source_timings: HashMap<SourceId, SourceTiming>,
+
+    /// The matcher used to filter and score the completions.
+    matcher: Arc<dyn Matcher>,
+
+    /// Ranks the completions that survived the filtering.
+    ranker: Arc<Ranker>,
+
+    /// The completions that survived the filtering of the last sorted
+    /// request.
+    filtered: Option<Filtered>,
+}
+
+/// The completions matching the prefix of a request.
+///
+/// A completion that doesn't match a prefix can't match a longer one either,
+/// so if the user keeps typing the next request only has to filter these
+/// completions instead of all the ones returned by the sources.
+struct Filtered {
+    request: Arc<CompletionRequest>,
+
+    /// The sources whose completions were filtered, sorted by id.
+    sources: Vec<SourceId>,
+
+    items: Vec<(SourceId, Arc<CompletionItem>)>,
+}
+
+impl Filtered {
+    /// Whether the prefix of `request` extends the prefix these completions
+    /// were filtered against.
+    fn is_extended_by(&self, request: &CompletionRequest) -> bool {
+        let (old, new) = (&self.request.position, &request.position);
+
+        self.request.document.buffer() == request.document.buffer()
+            && old.row == new.row
+            && old.col - old.len_prefix() == new.col - new.len_prefix()
+            && new.matched_prefix().starts_with(old.matched_prefix())
+    }
 }
 
 impl State {
     /// TODO: refactor
     #[inline]
-    pub(crate) fn new(sources: Vec<SourceBundle>, sender: CoreSender) -> Self {
+    pub(crate) fn new(
+        sources: Vec<SourceBundle>,
+        matcher: &MatcherConfig,
+        ranking: RankingWeights,
+        dedup: bool,
+        history: Vec<AcceptedCompletion>,
+        sender: CoreSender,
+    ) -> Self {
         let completions = sources
             .iter()
             .map(|source| {
@@ -73,17 +141,26 @@ impl State {
 
         // panic!("completions: {}", completions.len());
 
+        let ranker = Arc::new(Ranker::new(ranking, dedup, &sources, history));
+
         let sources = sources.into_iter().map(Arc::new).collect();
 
         let state = StateInner {
             sources,
             sender,
             buffer_sources: HashMap::new(),
+            documents: HashMap::new(),
+            detached_sources: HashMap::new(),
+            detached_everywhere: HashSet::new(),
             revision: Revision::default(),
             recompute_tasks: Vec::new(),
             resolve_tasks: Vec::new(),
             is_sending_completions: false,
             completions,
+            source_timings: HashMap::new(),
+            matcher: crate::sorter::matcher(matcher),
+            ranker,
+            filtered: None,
         };
 
         Self { inner: Arc::new(Mutex::new(state)) }
@@ -95,7 +172,13 @@ impl State {
 
         let state = &*self.inner.lock()?;
 
-        for source in state.sources.iter().map(Arc::clone) {
+        // Command-line documents are only completed by command-line sources
+        // and vice versa.
+        let sources = state.sources.iter().filter(|source| {
+            source.source.is_cmdline() == document.cmdline().is_some()
+        });
+
+        for source in sources.map(Arc::clone) {
             let cloned = self.clone();
             let doc = Arc::clone(&document);
             let sender = state.sender.clone();
@@ -134,78 +217,146 @@ impl State {
         document: Arc<Document>,
     ) -> Result<()> {
         let state = &mut *self.inner.lock()?;
-        let sources = &mut state.buffer_sources;
         let buffer = document.buffer();
 
-        match sources.get_mut(&buffer) {
-            Some(sources) => sources.push((source, trigger_chars)),
-
-            None => {
-                sources.insert(buffer, vec![(source, trigger_chars)]);
-                state.sender.send(CoreMessage::AttachDocument { document });
-            },
+        // The document is attached even if the source starts out detached so
+        // that the source can be re-attached later.
+        if !state.buffer_sources.contains_key(&buffer) {
+            state.buffer_sources.insert(buffer.clone(), Vec::new());
+            state.documents.insert(buffer.clone(), Arc::clone(&document));
+            state.sender.send(CoreMessage::AttachDocument { document });
         }
 
+        let sources = if state.detached_everywhere.contains(&source.id) {
+            state.detached_sources.entry(buffer).or_default()
+        } else {
+            state.buffer_sources.get_mut(&buffer).unwrap()
+        };
+
+        sources.push((source, trigger_chars));
+
         Ok(())
     }
 
     /// TODO: docs
     pub(crate) fn recompute_completions(
         &self,
-        request: CompletionRequest,
+        mut request: CompletionRequest,
     ) -> Result<()> {
+        let waiting = Instant::now();
         let state = &mut *self.inner.lock()?;
+        request.clock.time_lock_wait(waiting.elapsed());
         let request = Arc::new(request);
 
+        let previous_revision = state.revision;
         state.revision = request.id;
         state.is_sending_completions = true;
         state.recompute_tasks.drain(..).for_each(|task| task.abort());
         state.resolve_tasks.drain(..).for_each(|task| task.abort());
+        state.source_timings.clear();
 
         let mut cached_completions = Vec::new();
 
-        for (source, trigger_chars) in
-            state.buffer_sources.get(&request.document.buffer()).unwrap()
-        {
+        let sources =
+            state.buffer_sources.get(&request.document.buffer()).unwrap();
+
+        // All the sources could have been detached from this buffer.
+        if sources.is_empty() {
+            state.sender.send(CoreMessage::NoCompletions { id: request.id });
+            return Ok(());
+        }
+
+        let prefix_length = request.position.matched_prefix().chars().count();
+
+        for (source, trigger_chars) in sources {
             let (items, is_complete, revision) =
                 state.completions.get_mut(&source.id).unwrap();
 
             assert_ne!(state.revision, *revision);
 
-            if !source_should_recompute(&request, *is_complete, trigger_chars)
-            {
-                // If the previous completion results are still valid we just
-                // update the revision.
-                *revision = request.id;
-                cached_completions.extend(items.iter().map(Arc::clone));
-            } else {
+            // The source doesn't contribute to this request, and its previous
+            // completions have to be recomputed once the prefix gets long
+            // enough.
+            if prefix_length < source.limits.min_prefix_length {
+                items.clear();
+                *is_complete = false;
+                continue;
+            }
+
+            let trigger = self::completion_trigger(
+                &request,
+                *is_complete,
+                trigger_chars,
+            );
+
+            if let Some(trigger) = trigger {
                 let cloned = self.clone();
                 let source = Arc::clone(source);
                 let req = Arc::clone(&request);
 
                 let handle = tokio::spawn(async move {
-                    match source.complete(&req.document, &req.position).await {
-                        Ok(list) => cloned
-                            .on_completions_recomputed(list, source.id, req),
+                    let started = Instant::now();
+
+                    match source
+                        .complete(&req.document, &req.position, trigger)
+                        .await
+                    {
+                        Ok(list) => {
+                            let timing = SourceTiming {
+                                source: source.id,
+                                started,
+                                finished: Instant::now(),
+                                from_cache: false,
+                            };
+                            cloned.on_completions_recomputed(list, timing, req)
+                        },
 
                         Err(err) => cloned.complete_failed(source.id, err),
                     }
                 });
 
                 state.recompute_tasks.push(handle);
+            } else {
+                // If the previous completion results are still valid we just
+                // update the revision.
+                *revision = request.id;
+                state
+                    .source_timings
+                    .insert(source.id, SourceTiming::cached(source.id));
+                cached_completions.extend(
+                    items.iter().map(|item| (source.id, Arc::clone(item))),
+                );
             }
         }
 
-        if !cached_completions.is_empty() {
-            let mut clock = request.clock.clone();
-            clock.time_source_finished();
+        // If no source has to recompute its completions and the user has
+        // kept typing we only have to filter the previous results.
+        let filtered = state.filtered.take().filter(|filtered| {
+            state.recompute_tasks.is_empty()
+                && state.matcher.is_monotonic()
+                && filtered.request.id == previous_revision
+                && filtered.sources == state.current_sources()
+                && filtered.is_extended_by(&request)
+        });
 
-            let state = self.clone();
+        if let Some(filtered) = filtered {
+            if filtered.items.is_empty() {
+                state
+                    .sender
+                    .send(CoreMessage::NoCompletions { id: request.id });
+                state.filtered = Some(Filtered { request, ..filtered });
+                return Ok(());
+            }
 
-            let _ = std::thread::spawn(move || {
-                let sorted = crate::sort(cached_completions, &request);
-                state.on_completions_sorted(sorted, request, clock).unwrap();
-            });
+            cached_completions = filtered.items;
+        }
+
+        if !cached_completions.is_empty() {
+            let clock = request.clock.clone();
+            self.sort_completions(state, cached_completions, request, clock);
+        } else if state.recompute_tasks.is_empty() {
+            // E.g. the prefix is too short for any of the sources.
+            state.sender.send(CoreMessage::NoCompletions { id: request.id });
         }
 
         Ok(())
@@ -215,44 +366,78 @@ impl State {
     fn on_completions_recomputed(
         &self,
         list: CompletionList,
-        source: SourceId,
+        timing: SourceTiming,
         request: Arc<CompletionRequest>,
     ) -> Result<()> {
+        let waiting = Instant::now();
         let state = &mut *self.inner.lock()?;
+        let waited = waiting.elapsed();
 
         if request.id != state.revision {
             return Ok(());
         };
 
+        let source = timing.source;
+        state.source_timings.insert(source, timing);
+
+        let keyword_length = state.limits(source).keyword_length;
+
         let (current, is_complete, revision) =
             state.completions.get_mut(&source).unwrap();
 
         *is_complete = list.is_complete;
         *revision = request.id;
-        *current = list.items.into_par_iter().map(Arc::new).collect();
+        *current = list
+            .items
+            .into_par_iter()
+            .filter(|item| item.text.chars().count() >= keyword_length)
+            .map(Arc::new)
+            .collect();
 
         let completions = state
             .completions
-            .values()
-            .flat_map(|(items, _is_complete, revision)| {
+            .iter()
+            .flat_map(|(&source, (items, _is_complete, revision))| {
                 (*revision == state.revision)
                     .then_some(&**items)
                     .unwrap_or(&[])
+                    .iter()
+                    .map(move |item| (source, Arc::clone(item)))
             })
-            .map(Arc::clone)
             .collect::<Vec<_>>();
 
         let mut clock = request.clock.clone();
-        clock.time_source_finished();
+        clock.time_lock_wait(waited);
+
+        self.sort_completions(state, completions, request, clock);
+
+        Ok(())
+    }
+
+    /// Sorts the completions of the sources that are up to date with the last
+    /// revision on a separate thread.
+    fn sort_completions(
+        &self,
+        state: &StateInner,
+        completions: Vec<(SourceId, Arc<CompletionItem>)>,
+        request: Arc<CompletionRequest>,
+        mut clock: Clock,
+    ) {
+        clock.time_source_finished(state.source_timings.values().copied());
 
+        let sources = state.current_sources();
+        let matcher = Arc::clone(&state.matcher);
+        let ranker = Arc::clone(&state.ranker);
         let state = self.clone();
 
         let _ = std::thread::spawn(move || {
-            let sorted = crate::sort(completions, &request);
-            state.on_completions_sorted(sorted, request, clock).unwrap();
+            clock.time_sort_started();
+            let sorted =
+                crate::sort(completions, &request, &*matcher, &ranker);
+            state
+                .on_completions_sorted(sorted, request, clock, sources)
+                .unwrap();
         });
-
-        Ok(())
     }
 
     /// TODO: docs
@@ -261,12 +446,53 @@ impl State {
         items: Vec<ScoredCompletion>,
         request: Arc<CompletionRequest>,
         mut clock: Clock,
+        sources: Vec<SourceId>,
     ) -> Result<()> {
         clock.time_completions_sorted();
 
-        let state = &*self.inner.lock()?;
+        let filtered = Filtered {
+            request: Arc::clone(&request),
+            sources,
+            // The merged duplicates matched the prefix too, so they have to
+            // be merged again by the next requests.
+            items: items
+                .iter()
+                .flat_map(|completion| {
+                    std::iter::once((
+                        completion.source,
+                        Arc::clone(&completion.item),
+                    ))
+                    .chain(completion.duplicates.iter().cloned())
+                })
+                .collect(),
+        };
+
+        let waiting = Instant::now();
+        let state = &mut *self.inner.lock()?;
+        clock.time_lock_wait(waiting.elapsed());
+
+        if request.id != state.revision {
+            return Ok(());
+        }
+
+        // The sorting threads of the same revision can finish out of order,
+        // in which case we keep the results including more sources.
+        let is_newer = match &state.filtered {
+            Some(previous) if previous.request.id == request.id => {
+                filtered.sources.len() >= previous.sources.len()
+            },
+            _ => true,
+        };
+
+        if is_newer {
+            state.filtered = Some(filtered);
+        }
+
+        if state.is_sending_completions {
+            let items = crate::sorter::limit_items(items, |source| {
+                state.limits(source).max_items
+            });
 
-        if request.id == state.revision && state.is_sending_completions {
             let msg = if !items.is_empty() {
                 CoreMessage::Completions { items, request, clock }
             } else {
@@ -288,7 +514,15 @@ impl State {
     ) -> Result<()> {
         let state = &mut *self.inner.lock()?;
 
-        assert_eq!(state.revision, revision);
+        // The client could ask to resolve a completion from an older revision
+        // if the user selects it before the new completions arrive.
+        if revision != state.revision {
+            return Ok(());
+        }
+
+        // Only the last selected completion is shown in the details window,
+        // so any pending resolution is now stale.
+        state.resolve_tasks.drain(..).for_each(|task| task.abort());
 
         let source = state
             .buffer_sources
@@ -339,6 +573,42 @@ impl State {
         Ok(())
     }
 
+    /// Notifies the sources attached to a buffer that its text has been
+    /// edited. The detached sources are notified too, so that they're up to
+    /// date if they're re-attached.
+    pub(crate) fn buffer_edited(
+        &self,
+        buffer: Buffer,
+        edit: BufferEdit,
+    ) -> Result<()> {
+        let state = &*self.inner.lock()?;
+
+        let document = match state.documents.get(&buffer) {
+            Some(document) => document,
+            None => return Ok(()),
+        };
+
+        state
+            .buffer_sources
+            .get(&buffer)
+            .into_iter()
+            .chain(state.detached_sources.get(&buffer))
+            .flatten()
+            .for_each(|(source, _)| source.on_edit(document, &edit));
+
+        Ok(())
+    }
+
+    /// Records a completion accepted by the user.
+    pub(crate) fn record_accepted(
+        &self,
+        completion: &AcceptedCompletion,
+    ) -> Result<()> {
+        let state = &*self.inner.lock()?;
+        state.ranker.record_accepted(completion);
+        Ok(())
+    }
+
     /// TODO: docs
     pub(crate) fn stop_sending(&self, revision: Revision) -> Result<()> {
         let state = &mut *self.inner.lock().unwrap();
@@ -346,6 +616,58 @@ impl State {
         assert_eq!(state.revision, revision);
         state.is_sending_completions = false;
         state.recompute_tasks.drain(..).for_each(|task| task.abort());
+        state.resolve_tasks.drain(..).for_each(|task| task.abort());
+
+        Ok(())
+    }
+
+    /// Moves the sources that match `sources` (or all of them if `None`)
+    /// from `buffer_sources` to `detached_sources` for the given buffer, or
+    /// for every buffer if `buffer` is `None`.
+    pub(crate) fn detach_sources(
+        &self,
+        buffer: Option<Buffer>,
+        sources: Option<Vec<SourceId>>,
+    ) -> Result<()> {
+        let state = &mut *self.inner.lock()?;
+
+        if buffer.is_none() {
+            let ids = state.sources.iter().map(|source| source.id);
+            state
+                .detached_everywhere
+                .extend(ids.filter(|id| self::matches(&sources, id)));
+        }
+
+        self::move_sources(
+            &mut state.buffer_sources,
+            &mut state.detached_sources,
+            buffer.as_ref(),
+            &sources,
+        );
+
+        Ok(())
+    }
+
+    /// The inverse of [`detach_sources`](State::detach_sources).
+    pub(crate) fn reattach_sources(
+        &self,
+        buffer: Option<Buffer>,
+        sources: Option<Vec<SourceId>>,
+    ) -> Result<()> {
+        let state = &mut *self.inner.lock()?;
+
+        if buffer.is_none() {
+            state
+                .detached_everywhere
+                .retain(|id| !self::matches(&sources, id));
+        }
+
+        self::move_sources(
+            &mut state.detached_sources,
+            &mut state.buffer_sources,
+            buffer.as_ref(),
+            &sources,
+        );
 
         Ok(())
     }
@@ -368,22 +690,83 @@ impl State {
         error: GenericError,
     ) -> Result<()> {
         let state = &*self.inner.lock()?;
-        state.sender.send(CoreMessage::SourceCompleteFailed { source, error });
+        state.sender.send(CoreMessage::SourceResolveFailed { source, error });
         Ok(())
     }
 }
 
-/// TODO: docs
+impl StateInner {
+    /// Returns the sources whose completions are up to date with the last
+    /// revision, sorted by id.
+    fn current_sources(&self) -> Vec<SourceId> {
+        let mut sources =
+            self.source_timings.keys().copied().collect::<Vec<_>>();
+        sources.sort_unstable();
+        sources
+    }
+
+    /// Returns the limits of the source with the given id.
+    fn limits(&self, source: SourceId) -> SourceLimits {
+        self.sources
+            .iter()
+            .find(|bundle| bundle.id == source)
+            .map(|bundle| bundle.limits)
+            .unwrap_or_default()
+    }
+}
+
+/// Whether a source is included in a list of sources, where `None` means all
+/// of them.
 #[inline]
-fn source_should_recompute(
+fn matches(sources: &Option<Vec<SourceId>>, id: &SourceId) -> bool {
+    sources.as_ref().map(|sources| sources.contains(id)).unwrap_or(true)
+}
+
+/// Moves the sources matching `sources` of the given buffer, or of every
+/// buffer if `buffer` is `None`, from one map to the other.
+fn move_sources(
+    from: &mut HashMap<Buffer, Vec<AttachedSource>>,
+    to: &mut HashMap<Buffer, Vec<AttachedSource>>,
+    buffer: Option<&Buffer>,
+    sources: &Option<Vec<SourceId>>,
+) {
+    for (buf, attached) in from.iter_mut() {
+        if buffer.map(|buffer| buffer != buf).unwrap_or(false) {
+            continue;
+        }
+
+        let (moved, kept) =
+            attached.drain(..).partition::<Vec<_>, _>(|(source, _)| {
+                matches(sources, &source.id)
+            });
+
+        *attached = kept;
+
+        if !moved.is_empty() {
+            to.entry(buf.clone()).or_default().extend(moved);
+        }
+    }
+}
+
+/// Returns why a source should recompute its completions for a request, or
+/// `None` if its previous completions are still valid.
+#[inline]
+fn completion_trigger(
     request: &CompletionRequest,
     is_complete: IsComplete,
     trigger_chars: &[char],
-) -> bool {
+) -> Option<CompletionTrigger> {
     match request.kind {
-        RequestKind::RecomputeAll => true,
-        RequestKind::TypedCharacter(ch) => {
-            trigger_chars.contains(&ch) || !is_complete
+        RequestKind::Invoked => Some(CompletionTrigger::Invoked),
+
+        RequestKind::RecomputeAll => Some(CompletionTrigger::Automatic),
+
+        RequestKind::TypedCharacter(ch) if trigger_chars.contains(&ch) => {
+            Some(CompletionTrigger::TriggerCharacter(ch))
+        },
+
+        RequestKind::TypedCharacter(_) => {
+            (!is_complete).then_some(CompletionTrigger::Incomplete)
         },
     }
 }
@@ -1,14 +1,22 @@
+use completion_buffer as buffer;
 use completion_client as client;
-use completion_lipsum as lipsum;
+use completion_cmdline as cmdline;
+use completion_dictionary as dictionary;
 use completion_lsp as lsp;
+use completion_path as path;
+use completion_tags as tags;
 use nvim_oxi::{self as nvim, Dictionary};
 
 #[nvim::module]
 fn nvim_completion() -> nvim::Result<Dictionary> {
     client::register_source(lsp::Lsp);
-
-    // #[cfg(debug_assertions)]
-    client::register_source(lipsum::Lipsum);
+    client::register_source(buffer::BufferWords::default());
+    client::register_source(path::Paths);
+    client::register_source(tags::Tags::default());
+    client::register_source(dictionary::Dictionary::default());
+    client::register_source(cmdline::ExCommands);
+    client::register_source(cmdline::SearchHistory);
+    client::register_source(cmdline::CmdlineWords);
 
     Ok(client::build_api())
 }
@@ -0,0 +1,281 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use async_trait::async_trait;
+use completion_types::{
+    BufferEdit,
+    CompletionItem,
+    CompletionItemKind,
+    CompletionList,
+    CompletionSource,
+    CompletionTrigger,
+    Document,
+    Keyword,
+    Position,
+};
+use nvim_oxi::api::{self, Buffer};
+use serde::Deserialize;
+
+use crate::index::BufferIndex;
+use crate::Result;
+
+/// Completes the words contained in the current buffer and, optionally, in
+/// all the other listed buffers.
+#[derive(Default)]
+pub struct BufferWords {
+    indexes: Mutex<HashMap<Buffer, BufferIndex>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Which buffers to take the words from.
+    #[serde(default)]
+    buffers: Buffers,
+
+    /// The size in bytes over which a buffer is not indexed.
+    #[serde(default = "default_max_buffer_size")]
+    max_buffer_size: usize,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Buffers {
+    /// Only the buffer being edited.
+    #[default]
+    Current,
+
+    /// All the listed buffers (see `:h buflisted`).
+    Listed,
+}
+
+fn default_max_buffer_size() -> usize {
+    1024 * 1024
+}
+
+/// The text of a buffer read on the Neovim thread, waiting to be indexed.
+struct Snapshot {
+    buffer: Buffer,
+    keyword: Keyword,
+    lines: Vec<String>,
+    changedtick: Option<i64>,
+}
+
+impl Snapshot {
+    /// Reads the contents of a buffer, returning `None` if it's bigger than
+    /// `max_size` bytes. If `tracked` is set the index will be kept up to
+    /// date by the edits sent by the client, otherwise the buffer's
+    /// `b:changedtick` is saved to know when it has to be indexed again.
+    ///
+    /// Has to be called on the Neovim thread.
+    fn take(
+        buffer: Buffer,
+        max_size: usize,
+        tracked: bool,
+    ) -> Result<Option<Self>> {
+        let line_count = buffer.line_count()?;
+
+        if buffer.get_offset(line_count)? > max_size {
+            return Ok(None);
+        }
+
+        let keyword =
+            Keyword::parse(&buffer.get_option::<String>("iskeyword")?);
+
+        let changedtick = match tracked {
+            true => None,
+            false => Some(buffer.get_var::<i64>("changedtick")?),
+        };
+
+        let lines = buffer
+            .get_lines(0, line_count, false)?
+            .map(|line| line.to_string_lossy().into_owned())
+            .collect();
+
+        Ok(Some(Self { buffer, keyword, lines, changedtick }))
+    }
+
+    fn index(self) -> (Buffer, BufferIndex) {
+        let Self { buffer, keyword, lines, changedtick } = self;
+        (buffer, BufferIndex::new(keyword, lines, changedtick))
+    }
+}
+
+impl BufferWords {
+    fn indexes(&self) -> MutexGuard<'_, HashMap<Buffer, BufferIndex>> {
+        self.indexes.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Indexes the listed buffers other than the document's one that haven't been
+    /// indexed yet or that have changed since the last time they were, and
+    /// drops the indexes of the buffers that are not listed anymore or that
+    /// have been unloaded.
+    async fn index_listed(
+        &self,
+        document: &Document,
+        config: &Config,
+    ) -> Result<()> {
+        let current = document.buffer();
+        let max_size = config.max_buffer_size;
+
+        let indexed = self
+            .indexes()
+            .iter()
+            .map(|(buffer, index)| (buffer.clone(), index.changedtick()))
+            .collect::<HashMap<_, _>>();
+
+        let (loaded, listed, snapshots) = document
+            .client_sender
+            .on_nvim_thread(move || -> Result<_> {
+                let mut loaded = HashSet::new();
+                let mut listed = HashSet::new();
+                let mut snapshots = Vec::new();
+
+                for buffer in api::list_bufs() {
+                    if !buffer.is_loaded() {
+                        continue;
+                    }
+
+                    loaded.insert(buffer.clone());
+
+                    if buffer == current
+                        || !buffer.get_option::<bool>("buflisted")?
+                    {
+                        continue;
+                    }
+
+                    listed.insert(buffer.clone());
+
+                    let changedtick = buffer.get_var::<i64>("changedtick")?;
+
+                    let is_fresh = match indexed.get(&buffer) {
+                        Some(Some(tick)) => *tick == changedtick,
+                        Some(None) => true,
+                        None => false,
+                    };
+
+                    if !is_fresh {
+                        snapshots
+                            .extend(Snapshot::take(buffer, max_size, false)?);
+                    }
+                }
+
+                Ok((loaded, listed, snapshots))
+            })
+            .await?;
+
+        let mut indexes = self.indexes();
+
+        // The tracked buffers are kept even if they're not listed, but not
+        // once they've been unloaded or wiped out.
+        indexes.retain(|buffer, index| {
+            *buffer == document.buffer
+                || (loaded.contains(buffer)
+                    && (index.changedtick().is_none()
+                        || listed.contains(buffer)))
+        });
+
+        indexes.extend(snapshots.into_iter().map(Snapshot::index));
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CompletionSource for BufferWords {
+    const NAME: &'static str = "buffer";
+
+    type Config = Config;
+
+    type Error = crate::Error;
+
+    async fn enable(&self, doc: &Document, config: &Config) -> Result<bool> {
+        let buffer = doc.buffer();
+        let max_size = config.max_buffer_size;
+
+        let snapshot = doc
+            .client_sender
+            .on_nvim_thread(move || Snapshot::take(buffer, max_size, true))
+            .await?;
+
+        let is_indexed = snapshot.is_some();
+
+        if let Some(snapshot) = snapshot {
+            let (buffer, index) = snapshot.index();
+            self.indexes().insert(buffer, index);
+        }
+
+        // Even if the current buffer is too big to be indexed we can still
+        // complete the words of the other buffers.
+        Ok(is_indexed || matches!(config.buffers, Buffers::Listed))
+    }
+
+    async fn trigger_characters(
+        &self,
+        _doc: &Document,
+        _config: &Config,
+    ) -> Result<Vec<char>> {
+        Ok(Vec::new())
+    }
+
+    async fn complete(
+        &self,
+        doc: &Document,
+        pos: &Position,
+        _trigger: CompletionTrigger,
+        config: &Config,
+    ) -> Result<CompletionList> {
+        let current = doc.buffer();
+
+        if let Buffers::Listed = config.buffers {
+            self.index_listed(doc, config).await?;
+        }
+
+        let prefix = pos.matched_prefix();
+
+        let indexes = self.indexes();
+
+        let mut seen = HashSet::new();
+        let mut items = Vec::new();
+
+        if let Some(index) = indexes.get(&current) {
+            for (word, distance) in index.words_around(pos.row as usize) {
+                if word != prefix && seen.insert(word) {
+                    let item = CompletionItem::builder()
+                        .text(word)
+                        .kind(CompletionItemKind::Text)
+                        .distance(distance)
+                        .build();
+
+                    items.push(item);
+                }
+            }
+        }
+
+        let others = indexes
+            .iter()
+            .filter(|(buffer, _)| **buffer != current)
+            .filter(|_| matches!(config.buffers, Buffers::Listed));
+
+        for (_, index) in others {
+            for (word, _) in index.words_around(0) {
+                if word != prefix && seen.insert(word) {
+                    let item = CompletionItem::builder()
+                        .text(word)
+                        .kind(CompletionItemKind::Text)
+                        .build();
+
+                    items.push(item);
+                }
+            }
+        }
+
+        Ok(CompletionList { items, is_complete: true })
+    }
+
+    fn on_edit(&self, doc: &Document, edit: &BufferEdit, _config: &Config) {
+        if let Some(index) = self.indexes().get_mut(&doc.buffer) {
+            index.apply(edit);
+        }
+    }
+}
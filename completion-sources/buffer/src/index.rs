@@ -0,0 +1,159 @@
+use completion_types::{BufferEdit, Keyword};
+
+/// The words contained in a buffer, grouped by the line they appear in.
+#[derive(Debug)]
+pub(crate) struct BufferIndex {
+    /// The characters that make up a word in the buffer.
+    keyword: Keyword,
+
+    /// The words of each line of the buffer, without duplicates.
+    lines: Vec<Vec<String>>,
+
+    /// The `b:changedtick` of the buffer when it was indexed, or `None` if
+    /// the index is kept up to date by the edits sent by the client.
+    changedtick: Option<i64>,
+}
+
+impl BufferIndex {
+    pub(crate) fn new<L>(
+        keyword: Keyword,
+        lines: L,
+        changedtick: Option<i64>,
+    ) -> Self
+    where
+        L: IntoIterator,
+        L::Item: AsRef<str>,
+    {
+        let lines = lines
+            .into_iter()
+            .map(|line| self::words(&keyword, line.as_ref()))
+            .collect();
+
+        Self { keyword, lines, changedtick }
+    }
+
+    /// The `b:changedtick` of the buffer when it was indexed, or `None` if
+    /// the index is kept up to date by the edits sent by the client.
+    pub(crate) fn changedtick(&self) -> Option<i64> {
+        self.changedtick
+    }
+
+    /// Updates the index after the buffer has been edited.
+    pub(crate) fn apply(&mut self, edit: &BufferEdit) {
+        let start = edit.start.min(self.lines.len());
+        let end = (edit.start + edit.replaced).min(self.lines.len());
+
+        let words = edit
+            .lines
+            .iter()
+            .map(|line| self::words(&self.keyword, line))
+            .collect::<Vec<_>>();
+
+        self.lines.splice(start..end, words);
+    }
+
+    /// Returns an iterator over the words of the buffer together with the
+    /// distance in lines between them and `row`. The words closer to `row`
+    /// come first.
+    pub(crate) fn words_around(
+        &self,
+        row: usize,
+    ) -> impl Iterator<Item = (&str, u32)> + '_ {
+        let row = row.min(self.lines.len());
+
+        let (above, below) = self.lines.split_at(row);
+
+        let below = below.iter().enumerate();
+        let above =
+            above.iter().rev().enumerate().map(|(idx, l)| (idx + 1, l));
+
+        self::interleave(below, above).flat_map(|(distance, words)| {
+            words.iter().map(move |word| (&**word, distance as u32))
+        })
+    }
+}
+
+fn words(keyword: &Keyword, line: &str) -> Vec<String> {
+    let mut words = keyword.words(line).collect::<Vec<_>>();
+    words.sort_unstable();
+    words.dedup();
+    words.into_iter().map(ToOwned::to_owned).collect()
+}
+
+/// Alternates the items of two iterators until both are exhausted.
+fn interleave<I, J>(a: I, b: J) -> impl Iterator<Item = I::Item>
+where
+    I: Iterator,
+    J: Iterator<Item = I::Item>,
+{
+    let (mut a, mut b) = (a.fuse(), b.fuse());
+    let mut take_a = false;
+
+    std::iter::from_fn(move || {
+        take_a = !take_a;
+
+        match take_a {
+            true => a.next().or_else(|| b.next()),
+            false => b.next().or_else(|| a.next()),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(lines: &[&str]) -> BufferIndex {
+        BufferIndex::new(Keyword::default(), lines, None)
+    }
+
+    #[test]
+    fn apply_edits() {
+        let mut index = index(&["foo bar", "baz", "qux"]);
+
+        // Replacing a line.
+        index.apply(&BufferEdit {
+            start: 1,
+            replaced: 1,
+            lines: vec!["bazz".into()],
+        });
+        assert_eq!(
+            vec![vec!["bar", "foo"], vec!["bazz"], vec!["qux"]],
+            index.lines
+        );
+
+        // Splitting a line in two.
+        index.apply(&BufferEdit {
+            start: 0,
+            replaced: 1,
+            lines: vec!["foo".into(), "bar".into()],
+        });
+        assert_eq!(
+            vec![vec!["foo"], vec!["bar"], vec!["bazz"], vec!["qux"]],
+            index.lines
+        );
+
+        // Joining the last two lines.
+        index.apply(&BufferEdit {
+            start: 2,
+            replaced: 2,
+            lines: vec!["bazz qux".into()],
+        });
+        assert_eq!(
+            vec![vec!["foo"], vec!["bar"], vec!["bazz", "qux"]],
+            index.lines
+        );
+    }
+
+    #[test]
+    fn words_around() {
+        let index = index(&["a", "b", "c", "d", "e"]);
+
+        let words = index.words_around(3).collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![("d", 0), ("c", 1), ("e", 1), ("b", 2), ("a", 3)],
+            words
+        );
+    }
+}
@@ -0,0 +1,6 @@
+mod buffer_words;
+mod error;
+mod index;
+
+pub use buffer_words::BufferWords;
+use error::{Error, Result};
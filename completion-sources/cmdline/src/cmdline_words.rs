@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use completion_types::{
+    CmdlineType,
+    CompletionItem,
+    CompletionItemKind,
+    CompletionList,
+    CompletionSource,
+    CompletionTrigger,
+    Document,
+    Keyword,
+    Position,
+};
+use nvim_oxi::api::Buffer;
+use serde::Deserialize;
+
+use crate::Result;
+
+/// Completes searches with the words of the buffer being searched.
+pub struct CmdlineWords;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Buffers bigger than this many bytes are not read.
+    #[serde(default = "one_mebibyte")]
+    max_buffer_size: usize,
+}
+
+fn one_mebibyte() -> usize {
+    1 << 20
+}
+
+#[async_trait]
+impl CompletionSource for CmdlineWords {
+    const NAME: &'static str = "cmdline_words";
+
+    const CMDLINE: bool = true;
+
+    type Config = Config;
+
+    type Error = crate::Error;
+
+    async fn enable(&self, doc: &Document, _config: &Config) -> Result<bool> {
+        Ok(doc.cmdline() == Some(CmdlineType::Search))
+    }
+
+    async fn trigger_characters(
+        &self,
+        _doc: &Document,
+        _config: &Config,
+    ) -> Result<Vec<char>> {
+        Ok(Vec::new())
+    }
+
+    async fn complete(
+        &self,
+        doc: &Document,
+        pos: &Position,
+        _trigger: CompletionTrigger,
+        config: &Config,
+    ) -> Result<CompletionList> {
+        let prefix = pos.matched_prefix();
+
+        // Every word of the buffer would match, so we wait for the user to
+        // type the first character.
+        if prefix.is_empty() {
+            return Ok(CompletionList {
+                items: Vec::new(),
+                is_complete: false,
+            });
+        }
+
+        let max_size = config.max_buffer_size;
+
+        // The document's buffer is a scratch buffer, the one being searched
+        // is the current one.
+        let (keyword, lines) = doc
+            .client_sender
+            .on_nvim_thread(move || self::lines(&Buffer::current(), max_size))
+            .await?;
+
+        let mut seen = HashSet::new();
+
+        let items = lines
+            .iter()
+            .flat_map(|line| keyword.words(line))
+            .filter(|word| *word != prefix && seen.insert(*word))
+            .map(|word| {
+                CompletionItem::builder()
+                    .text(word)
+                    .kind(CompletionItemKind::Text)
+                    .build()
+            })
+            .collect();
+
+        Ok(CompletionList { items, is_complete: true })
+    }
+}
+
+/// Returns the lines of a buffer together with the characters that make up
+/// its words, or no lines if it's bigger than `max_size` bytes.
+///
+/// Has to be called on the Neovim thread.
+fn lines(buffer: &Buffer, max_size: usize) -> Result<(Keyword, Vec<String>)> {
+    let keyword = Keyword::parse(&buffer.get_option::<String>("iskeyword")?);
+
+    let line_count = buffer.line_count()?;
+
+    if buffer.get_offset(line_count)? > max_size {
+        return Ok((keyword, Vec::new()));
+    }
+
+    let lines = buffer
+        .get_lines(0, line_count, false)?
+        .map(|line| line.to_string_lossy().into_owned())
+        .collect();
+
+    Ok((keyword, lines))
+}
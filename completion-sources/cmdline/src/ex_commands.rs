@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use completion_types::{
+    CmdlineType,
+    CompletionList,
+    CompletionSource,
+    CompletionTrigger,
+    Document,
+    Position,
+};
+use nvim_oxi::api;
+use serde::Deserialize;
+
+use crate::{item, Result};
+
+/// Completes Ex commands and their arguments using the same completions
+/// Neovim shows when pressing `<Tab>` on the command-line.
+pub struct ExCommands;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {}
+
+#[async_trait]
+impl CompletionSource for ExCommands {
+    const NAME: &'static str = "cmdline";
+
+    const CMDLINE: bool = true;
+
+    type Config = Config;
+
+    type Error = crate::Error;
+
+    async fn enable(&self, doc: &Document, _config: &Config) -> Result<bool> {
+        Ok(doc.cmdline() == Some(CmdlineType::Command))
+    }
+
+    async fn trigger_characters(
+        &self,
+        _doc: &Document,
+        _config: &Config,
+    ) -> Result<Vec<char>> {
+        Ok(Vec::new())
+    }
+
+    async fn complete(
+        &self,
+        doc: &Document,
+        pos: &Position,
+        _trigger: CompletionTrigger,
+        _config: &Config,
+    ) -> Result<CompletionList> {
+        let before_cursor = pos.line[..pos.col].to_owned();
+
+        let completions = doc
+            .client_sender
+            .on_nvim_thread(move || {
+                api::call_function::<_, Vec<String>>(
+                    "getcompletion",
+                    (before_cursor, "cmdline"),
+                )
+            })
+            .await?;
+
+        let before_cursor = &pos.line[..pos.col];
+
+        let items = completions
+            .into_iter()
+            .filter_map(|completion| {
+                let start = self::argument_start(before_cursor, &completion);
+                item::replacing(pos, start, completion)
+            })
+            .collect();
+
+        // What's being completed depends on the whole command-line, e.g.
+        // typing a space moves to the next argument, so the completions are
+        // recomputed on every change.
+        Ok(CompletionList { items, is_complete: false })
+    }
+}
+
+/// Returns the byte offset in `before_cursor` where the text replaced by a
+/// completion starts. That's the start of the longest suffix of the argument
+/// being typed that the completion starts with (e.g. the `r` of `ft=r` for
+/// `rust`), or the start of the argument if there isn't one.
+fn argument_start(before_cursor: &str, completion: &str) -> usize {
+    let arg_start =
+        before_cursor.rfind([' ', '\t']).map(|idx| idx + 1).unwrap_or(0);
+
+    before_cursor[arg_start..]
+        .char_indices()
+        .map(|(idx, _)| arg_start + idx)
+        .find(|&idx| completion.starts_with(&before_cursor[idx..]))
+        .unwrap_or(arg_start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_of_argument() {
+        assert_eq!(0, argument_start("ed", "edit"));
+        assert_eq!(2, argument_start("e src/ma", "src/main.rs"));
+        assert_eq!(7, argument_start("set ft=r", "rust"));
+        assert_eq!(5, argument_start("10,20s", "substitute"));
+        assert_eq!(4, argument_start("set ", "all"));
+        assert_eq!(4, argument_start("set xyz", "all"));
+    }
+}
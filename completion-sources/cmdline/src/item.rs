@@ -0,0 +1,52 @@
+use completion_types::{CompletionItem, CompletionTextEdit, Position};
+
+/// Returns a completion item replacing the command-line from `start` to the
+/// cursor with `text`, or `None` if the text between `start` and the matched
+/// prefix isn't the start of `text`.
+pub(crate) fn replacing(
+    pos: &Position,
+    start: usize,
+    text: String,
+) -> Option<CompletionItem> {
+    // The matched prefix can start before the replaced text (e.g. at the `~`
+    // of `~/`) or after it (e.g. after the `.` of `foo.rs`).
+    let matched_start = pos.col - pos.len_prefix();
+
+    let filter_text = match matched_start <= start {
+        true => format!("{}{text}", &pos.line[matched_start..start]),
+
+        false => {
+            let typed = &pos.line[start..matched_start];
+            text.strip_prefix(typed)?.to_owned()
+        },
+    };
+
+    let edit = CompletionTextEdit {
+        text: text.clone(),
+        insert: start..pos.col,
+        replace: start..pos.col,
+    };
+
+    let item = CompletionItem::builder()
+        .text(text)
+        .filter_text(filter_text)
+        .text_edit(edit)
+        .build();
+
+    Some(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_text() {
+        let pos = Position::new(0, 8, "e foo.rs");
+
+        let item = replacing(&pos, 2, "foo.rs".to_owned()).unwrap();
+        assert_eq!("rs", item.filter_text());
+
+        assert!(replacing(&pos, 2, "bar.rs".to_owned()).is_none());
+    }
+}
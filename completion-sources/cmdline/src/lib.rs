@@ -0,0 +1,10 @@
+mod cmdline_words;
+mod error;
+mod ex_commands;
+mod item;
+mod search_history;
+
+pub use cmdline_words::CmdlineWords;
+use error::{Error, Result};
+pub use ex_commands::ExCommands;
+pub use search_history::SearchHistory;
@@ -0,0 +1,99 @@
+use async_trait::async_trait;
+use completion_types::{
+    CmdlineType,
+    CompletionList,
+    CompletionSource,
+    CompletionTrigger,
+    Document,
+    Position,
+};
+use nvim_oxi::api;
+use serde::Deserialize;
+
+use crate::{item, Result};
+
+/// Completes the searches done in this and the previous sessions, replacing
+/// the whole search pattern.
+pub struct SearchHistory;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The maximum number of searches to complete, starting from the most
+    /// recent one.
+    #[serde(default = "fifty")]
+    max_entries: usize,
+}
+
+fn fifty() -> usize {
+    50
+}
+
+#[async_trait]
+impl CompletionSource for SearchHistory {
+    const NAME: &'static str = "search_history";
+
+    const CMDLINE: bool = true;
+
+    type Config = Config;
+
+    type Error = crate::Error;
+
+    async fn enable(&self, doc: &Document, _config: &Config) -> Result<bool> {
+        Ok(doc.cmdline() == Some(CmdlineType::Search))
+    }
+
+    async fn trigger_characters(
+        &self,
+        _doc: &Document,
+        _config: &Config,
+    ) -> Result<Vec<char>> {
+        Ok(Vec::new())
+    }
+
+    async fn complete(
+        &self,
+        doc: &Document,
+        pos: &Position,
+        _trigger: CompletionTrigger,
+        config: &Config,
+    ) -> Result<CompletionList> {
+        let max_entries = config.max_entries;
+
+        let entries = doc
+            .client_sender
+            .on_nvim_thread(move || self::history(max_entries))
+            .await?;
+
+        let items = entries
+            .into_iter()
+            .filter(|entry| *entry != pos.line)
+            .filter_map(|entry| item::replacing(pos, 0, entry))
+            .collect();
+
+        Ok(CompletionList { items, is_complete: true })
+    }
+}
+
+/// Returns the most recent `max_entries` entries of the search history,
+/// from the newest to the oldest.
+///
+/// Has to be called on the Neovim thread.
+fn history(max_entries: usize) -> Result<Vec<String>> {
+    // This is -1 if the history is disabled, e.g. with `'history'` set to 0.
+    let len = api::call_function::<_, i64>("histnr", ("search",))?;
+
+    let mut entries = Vec::new();
+
+    for idx in (1..=len).rev().take(max_entries) {
+        let entry =
+            api::call_function::<_, String>("histget", ("search", idx))?;
+
+        // Entries can be deleted via `histdel()`.
+        if !entry.is_empty() {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
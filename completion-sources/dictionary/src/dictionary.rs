@@ -0,0 +1,306 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use async_trait::async_trait;
+use completion_types::{
+    CompletionItem,
+    CompletionItemKind,
+    CompletionList,
+    CompletionSource,
+    CompletionTrigger,
+    Document,
+    Position,
+};
+use nvim_oxi::api::{self, Buffer};
+use serde::Deserialize;
+use tokio::task;
+
+use crate::word_list::WordList;
+use crate::{spell, Error, Result};
+
+/// Completes the words contained in dictionary files, e.g. to complete
+/// English in Markdown files or git commits.
+#[derive(Default)]
+pub struct Dictionary {
+    /// The word lists read so far, indexed by the path of their file.
+    lists: Mutex<HashMap<PathBuf, Arc<WordList>>>,
+
+    /// The word lists used to complete each buffer.
+    buffers: Mutex<HashMap<Buffer, Vec<Arc<WordList>>>>,
+
+    /// The files that couldn't be read, which are only reported once.
+    unreadable: Mutex<HashSet<PathBuf>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The word list files used for each filetype. The words can be separated
+    /// by any whitespace, e.g. one word per line.
+    #[serde(default)]
+    filetypes: HashMap<String, Vec<PathBuf>>,
+
+    /// Whether to also use the files listed in the `dictionary` option.
+    #[serde(default = "yes")]
+    dictionary: bool,
+
+    /// Whether to also use the spell files of the languages in `spelllang`
+    /// in the buffers where spell checking is enabled.
+    #[serde(default = "yes")]
+    spell: bool,
+}
+
+fn yes() -> bool {
+    true
+}
+
+/// Echoes the files that couldn't be read as warnings.
+///
+/// Has to be called on the Neovim thread.
+fn warn(warnings: &[String]) {
+    for warning in warnings {
+        let chunks =
+            [("[nvim-completion] ", Some("WarningMsg")), (warning, None)];
+        let _ = api::echo(chunks, true);
+    }
+}
+
+/// The files a buffer takes its words from.
+#[derive(Default)]
+struct Files {
+    /// Files containing whitespace separated words.
+    plain: Vec<PathBuf>,
+
+    /// Neovim's `.spl` spell files.
+    spell: Vec<PathBuf>,
+}
+
+impl Dictionary {
+    fn lists(&self) -> MutexGuard<'_, HashMap<PathBuf, Arc<WordList>>> {
+        self.lists.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn buffers(&self) -> MutexGuard<'_, HashMap<Buffer, Vec<Arc<WordList>>>> {
+        self.buffers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn unreadable(&self) -> MutexGuard<'_, HashSet<PathBuf>> {
+        self.unreadable.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the word list of a file, reading it if it's the first time
+    /// it's used. Returns `None` for spell files we can't read.
+    async fn word_list(
+        &self,
+        path: PathBuf,
+        is_spell: bool,
+    ) -> Result<Option<Arc<WordList>>> {
+        if let Some(list) = self.lists().get(&path) {
+            return Ok(Some(Arc::clone(list)));
+        }
+
+        // Indexing a dictionary means sorting up to a few hundred thousand
+        // words, so it's done on a thread where blocking is fine.
+        let list = task::spawn_blocking({
+            let path = path.clone();
+            move || match is_spell {
+                true => Ok(std::fs::read(&path)
+                    .ok()
+                    .and_then(|bytes| spell::read_words(&bytes))
+                    .map(WordList::from_words)),
+
+                false => WordList::open(&path)
+                    .map(Some)
+                    .map_err(|source| Error::Io { path, source }),
+            }
+        })
+        .await
+        .unwrap()?;
+
+        let list = list.map(Arc::new);
+
+        if let Some(list) = &list {
+            self.lists().insert(path, Arc::clone(list));
+        }
+
+        Ok(list)
+    }
+}
+
+#[async_trait]
+impl CompletionSource for Dictionary {
+    const NAME: &'static str = "dictionary";
+
+    type Config = Config;
+
+    type Error = Error;
+
+    async fn enable(&self, doc: &Document, config: &Config) -> Result<bool> {
+        let buffer = doc.buffer();
+        let (dictionary, spell) = (config.dictionary, config.spell);
+
+        let mut files = doc
+            .client_sender
+            .on_nvim_thread(move || self::files(&buffer, dictionary, spell))
+            .await?;
+
+        if let Some(paths) = config.filetypes.get(doc.filetype()) {
+            files.plain.extend(paths.iter().cloned());
+        }
+
+        let mut lists = Vec::new();
+        let mut warnings = Vec::new();
+
+        for path in files.plain {
+            match self.word_list(path.clone(), false).await {
+                Ok(list) => lists.extend(list),
+
+                // A missing file shouldn't keep the other ones from being
+                // used.
+                Err(err @ Error::Io { .. }) => {
+                    if self.unreadable().insert(path) {
+                        warnings.push(err.to_string());
+                    }
+                },
+
+                Err(err) => return Err(err),
+            }
+        }
+
+        if !warnings.is_empty() {
+            doc.client_sender
+                .on_nvim_thread(move || self::warn(&warnings))
+                .await;
+        }
+
+        for path in files.spell {
+            lists.extend(self.word_list(path, true).await?);
+        }
+
+        let is_enabled = !lists.is_empty();
+
+        self.buffers().insert(doc.buffer(), lists);
+
+        Ok(is_enabled)
+    }
+
+    async fn trigger_characters(
+        &self,
+        _doc: &Document,
+        _config: &Config,
+    ) -> Result<Vec<char>> {
+        Ok(Vec::new())
+    }
+
+    async fn complete(
+        &self,
+        doc: &Document,
+        pos: &Position,
+        _trigger: CompletionTrigger,
+        _config: &Config,
+    ) -> Result<CompletionList> {
+        let prefix = pos.matched_prefix();
+
+        // Only the words starting with the prefix are looked up, so we wait
+        // for the user to type the first character.
+        if prefix.is_empty() {
+            return Ok(CompletionList {
+                items: Vec::new(),
+                is_complete: false,
+            });
+        }
+
+        let capitalize = prefix.starts_with(char::is_uppercase);
+
+        let buffers = self.buffers();
+
+        let lists = match buffers.get(&doc.buffer) {
+            Some(lists) => lists,
+            None => {
+                return Ok(CompletionList {
+                    items: Vec::new(),
+                    is_complete: true,
+                })
+            },
+        };
+
+        let mut seen = HashSet::new();
+
+        let items = lists
+            .iter()
+            .flat_map(|list| list.with_prefix(prefix))
+            .filter(|word| *word != prefix && seen.insert(*word))
+            .map(|word| {
+                let text = match capitalize {
+                    true => self::capitalize(word),
+                    false => word.to_owned(),
+                };
+
+                CompletionItem::builder()
+                    .text(text)
+                    .kind(CompletionItemKind::Text)
+                    .build()
+            })
+            .collect();
+
+        Ok(CompletionList { items, is_complete: true })
+    }
+}
+
+/// Returns the files listed in the `dictionary` option and the spell files
+/// of the languages in `spelllang` if spell checking is enabled.
+///
+/// Has to be called on the Neovim thread.
+fn files(buffer: &Buffer, dictionary: bool, spell: bool) -> Result<Files> {
+    buffer
+        .call(move |()| {
+            let mut files = Files::default();
+
+            if dictionary {
+                let option =
+                    api::call_function::<_, String>("eval", ("&dictionary",))?;
+
+                for file in option.split(',').filter(|file| !file.is_empty()) {
+                    let path =
+                        api::call_function::<_, String>("expand", (file,))?;
+                    files.plain.push(path.into());
+                }
+            }
+
+            if spell && api::call_function::<_, i64>("eval", ("&spell",))? != 0
+            {
+                let option =
+                    api::call_function::<_, String>("eval", ("&spelllang",))?;
+
+                // Regions are stored in the same spell file as the language
+                // (e.g. `en_us` is in `en.utf-8.spl`).
+                let langs = option
+                    .split(',')
+                    .filter_map(|lang| lang.split('_').next())
+                    .filter(|lang| !lang.is_empty() && *lang != "cjk");
+
+                for lang in langs {
+                    let name = format!("spell/{lang}.utf-8.spl");
+                    let paths = api::call_function::<_, Vec<String>>(
+                        "nvim_get_runtime_file",
+                        (name, true),
+                    )?;
+                    files.spell.extend(paths.into_iter().map(PathBuf::from));
+                }
+            }
+
+            Ok(files)
+        })
+        .map_err(Into::into)
+}
+
+/// Uppercases the first character of a word.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -0,0 +1,17 @@
+use std::path::PathBuf;
+
+use thiserror::Error as ThisError;
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, ThisError)]
+pub enum Error {
+    #[error("couldn't read dictionary {path:?}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+
+    #[error(transparent)]
+    Nvim(#[from] nvim_oxi::Error),
+
+    #[error(transparent)]
+    NvimApi(#[from] nvim_oxi::api::Error),
+}
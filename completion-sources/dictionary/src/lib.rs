@@ -0,0 +1,7 @@
+mod dictionary;
+mod error;
+mod spell;
+mod word_list;
+
+pub use dictionary::Dictionary;
+use error::{Error, Result};
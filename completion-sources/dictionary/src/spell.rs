@@ -0,0 +1,302 @@
+//! Extracts the words contained in Neovim's `.spl` spell files.
+//!
+//! A spell file starts with the `VIMspell` magic string and a version byte,
+//! followed by a list of sections terminated by `SN_END` and then by the
+//! tree of case-folded words, the tree of keep-case words and the tree of
+//! prefixes. See `:h spell-file-format` and `spellfile.c` in Neovim's
+//! source for the details.
+
+/// The magic string at the start of every spell file.
+const MAGIC: &[u8] = b"VIMspell";
+
+/// The only version of the format we know how to read.
+const VERSION: u8 = 50;
+
+/// Marks the end of the sections.
+const SN_END: u8 = 255;
+
+/// End of a word without flags.
+const BY_NOFLAGS: u8 = 0;
+
+/// A child node that's shared with another node, whose index follows.
+const BY_INDEX: u8 = 1;
+
+/// End of a word, followed by the `<flags>` byte.
+const BY_FLAGS: u8 = 2;
+
+/// End of a word, followed by the `<flags>` and `<flags2>` bytes.
+const BY_FLAGS2: u8 = 3;
+
+/// The maximum length in bytes of a word, used to stop walking a malformed
+/// tree whose shared nodes form a cycle or that's too deep to be read without
+/// overflowing the stack.
+const MAX_WORD_LEN: usize = 254;
+
+/// The word is followed by a `<region>` byte.
+const WF_REGION: u8 = 0x01;
+
+/// The word is not a valid word and shouldn't be completed.
+const WF_BANNED: u8 = 0x10;
+
+/// The word is followed by an `<affixID>` byte.
+const WF_AFX: u8 = 0x20;
+
+/// Returns the words contained in a spell file, or `None` if the file is
+/// malformed or uses a version of the format we don't support.
+pub(crate) fn read_words(bytes: &[u8]) -> Option<Vec<String>> {
+    let mut reader = Reader { bytes, pos: 0 };
+
+    if reader.take(MAGIC.len())? != MAGIC || reader.byte()? != VERSION {
+        return None;
+    }
+
+    // Skip the sections, which contain the regions, the character tables and
+    // other metadata we don't need.
+    loop {
+        let id = reader.byte()?;
+
+        if id == SN_END {
+            break;
+        }
+
+        let _flags = reader.byte()?;
+        let len = reader.u32()?;
+        reader.take(len as usize)?;
+    }
+
+    let mut words = Vec::new();
+
+    // Both the case-folded and the keep-case words are completed.
+    for _ in 0..2 {
+        let tree = Tree::read(&mut reader)?;
+        tree.words(&mut words);
+    }
+
+    Some(words)
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.bytes.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        self.take(1).map(|bytes| bytes[0])
+    }
+
+    fn u24(&mut self) -> Option<u32> {
+        let bytes = self.take(3)?;
+        Some(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.take(4)?;
+        Some(u32::from_be_bytes(bytes.try_into().ok()?))
+    }
+}
+
+/// A tree of words stored like Neovim does in memory: every node is a
+/// sibling count followed by the bytes of the siblings, and the index of the
+/// child of each sibling is stored at the same position in `idxs`. A zero
+/// byte marks the end of a word.
+struct Tree {
+    byts: Vec<u8>,
+    idxs: Vec<u32>,
+    banned: Vec<bool>,
+}
+
+impl Tree {
+    fn read(reader: &mut Reader<'_>) -> Option<Self> {
+        let len = reader.u32()? as usize;
+
+        // Every entry of the tree takes at least a byte in the file, so we
+        // can tell a garbage length before allocating for it.
+        if len > reader.bytes.len() - reader.pos {
+            return None;
+        }
+
+        let mut tree = Self {
+            byts: vec![0; len],
+            idxs: vec![0; len],
+            banned: vec![false; len],
+        };
+
+        if len > 0 {
+            tree.read_node(reader, 0, 0)?;
+        }
+
+        Some(tree)
+    }
+
+    /// Reads the node starting at `start`, `depth` levels below the root,
+    /// returning the index right after it and its children.
+    fn read_node(
+        &mut self,
+        reader: &mut Reader<'_>,
+        start: usize,
+        depth: usize,
+    ) -> Option<usize> {
+        let len = reader.byte()? as usize;
+
+        if len == 0 || start + len >= self.byts.len() || depth > MAX_WORD_LEN {
+            return None;
+        }
+
+        self.byts[start] = len as u8;
+
+        let mut shared = vec![false; len];
+
+        for (i, is_shared) in shared.iter_mut().enumerate() {
+            let idx = start + 1 + i;
+
+            let byte = match reader.byte()? {
+                BY_NOFLAGS => 0,
+
+                BY_INDEX => {
+                    self.idxs[idx] = reader.u24()?;
+                    *is_shared = true;
+                    reader.byte()?
+                },
+
+                kind @ (BY_FLAGS | BY_FLAGS2) => {
+                    let flags = reader.byte()?;
+
+                    if kind == BY_FLAGS2 {
+                        reader.byte()?;
+                    }
+                    if flags & WF_REGION != 0 {
+                        reader.byte()?;
+                    }
+                    if flags & WF_AFX != 0 {
+                        reader.byte()?;
+                    }
+
+                    self.banned[idx] = flags & WF_BANNED != 0;
+                    0
+                },
+
+                byte => byte,
+            };
+
+            self.byts[idx] = byte;
+        }
+
+        let mut next = start + 1 + len;
+
+        for (i, is_shared) in shared.into_iter().enumerate() {
+            let idx = start + 1 + i;
+
+            if self.byts[idx] != 0 && !is_shared {
+                self.idxs[idx] = next as u32;
+                next = self.read_node(reader, next, depth + 1)?;
+            }
+        }
+
+        Some(next)
+    }
+
+    /// Pushes all the words of the tree to `words`.
+    fn words(&self, words: &mut Vec<String>) {
+        if self.byts.is_empty() {
+            return;
+        }
+
+        // Walk the tree depth first with an explicit stack, since the words
+        // can be long enough to blow the stack if we recursed.
+        let mut word = Vec::new();
+        let mut stack = vec![(0, 0)];
+
+        while let Some((node, i)) = stack.pop() {
+            let len = self.byts[node] as usize;
+
+            if i == len || node + 1 + i >= self.byts.len() {
+                word.pop();
+                continue;
+            }
+
+            stack.push((node, i + 1));
+
+            let idx = node + 1 + i;
+
+            match self.byts[idx] {
+                0 => {
+                    if !self.banned[idx] {
+                        words.extend(String::from_utf8(word.clone()).ok());
+                    }
+                },
+
+                byte => match self.idxs.get(idx) {
+                    Some(&child)
+                        if (child as usize) < self.byts.len()
+                            && word.len() < MAX_WORD_LEN =>
+                    {
+                        word.push(byte);
+                        stack.push((child as usize, 0));
+                    },
+                    _ => {},
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_spell_file() {
+        let mut bytes = Vec::new();
+
+        bytes.extend(MAGIC);
+        bytes.push(VERSION);
+
+        // A section with a 2 bytes payload, then the end of the sections.
+        bytes.extend([1, 0, 0, 0, 0, 2, b'x', b'y']);
+        bytes.push(SN_END);
+
+        // The case-folded words "a", "ab" and the banned "ac".
+        bytes.extend(10u32.to_be_bytes());
+        bytes.extend([1, b'a']);
+        bytes.extend([3, BY_NOFLAGS, b'b', b'c']);
+        bytes.extend([1, BY_NOFLAGS]);
+        bytes.extend([1, BY_FLAGS, WF_BANNED]);
+
+        // No keep-case words.
+        bytes.extend(0u32.to_be_bytes());
+
+        assert_eq!(
+            Some(vec!["a".to_owned(), "ab".to_owned()]),
+            read_words(&bytes)
+        );
+
+        assert_eq!(None, read_words(b"VIMspell\x31"));
+    }
+
+    #[test]
+    fn too_deep_tree() {
+        let mut bytes = Vec::new();
+
+        bytes.extend(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(SN_END);
+
+        // A single word longer than any valid one.
+        let depth = MAX_WORD_LEN + 2;
+        bytes.extend((2 * depth as u32 + 3).to_be_bytes());
+        for _ in 0..depth {
+            bytes.extend([1, b'a']);
+        }
+        bytes.extend([1, BY_NOFLAGS]);
+        bytes.extend(0u32.to_be_bytes());
+
+        assert_eq!(None, read_words(&bytes));
+    }
+}
@@ -0,0 +1,153 @@
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+/// A list of words indexed by prefix. Words are compared ignoring the case
+/// of ASCII letters.
+pub(crate) struct WordList {
+    /// The text containing the words.
+    text: Text,
+
+    /// The byte ranges of the words in the text, sorted by word.
+    words: Vec<Range<u32>>,
+}
+
+enum Text {
+    /// A word list read from a file, e.g. `/usr/share/dict/words`, whose
+    /// words are separated by whitespace.
+    Mapped(Mmap),
+
+    /// The words extracted from a binary file (e.g. a spell file) and joined
+    /// together.
+    Owned(Vec<u8>),
+}
+
+impl AsRef<[u8]> for Text {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Mapped(mmap) => mmap,
+            Self::Owned(bytes) => bytes,
+        }
+    }
+}
+
+impl WordList {
+    /// Memory-maps a word list file and indexes its words.
+    pub(crate) fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+
+        // Safety: modifying or truncating the file from another process
+        // while it's mapped is undefined behavior. Word lists are expected to
+        // be static files, so we accept that risk to avoid reading whole
+        // dictionaries in memory.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        if u32::try_from(mmap.len()).is_err() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "word lists bigger than 4GB are not supported",
+            ));
+        }
+
+        let words = mmap
+            .split(|byte| byte.is_ascii_whitespace())
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                let start = word.as_ptr() as usize - mmap.as_ptr() as usize;
+                start as u32..(start + word.len()) as u32
+            })
+            .collect();
+
+        Ok(Self::new(Text::Mapped(mmap), words))
+    }
+
+    /// Indexes a list of words.
+    pub(crate) fn from_words<I>(words: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut text = Vec::new();
+        let mut ranges = Vec::new();
+
+        for word in words {
+            let start = text.len() as u32;
+            text.extend_from_slice(word.as_ref().as_bytes());
+            ranges.push(start..text.len() as u32);
+        }
+
+        Self::new(Text::Owned(text), ranges)
+    }
+
+    fn new(text: Text, mut words: Vec<Range<u32>>) -> Self {
+        let bytes = text.as_ref();
+
+        let word = |range: &Range<u32>| {
+            bytes.get(range.start as usize..range.end as usize).unwrap_or(&[])
+        };
+
+        words.sort_unstable_by(|a, b| self::compare(word(a), word(b)));
+        words.dedup_by(|a, b| word(a) == word(b));
+
+        Self { text, words }
+    }
+
+    fn word(&self, range: &Range<u32>) -> &[u8] {
+        self.text
+            .as_ref()
+            .get(range.start as usize..range.end as usize)
+            .unwrap_or(&[])
+    }
+
+    /// Returns an iterator over the words starting with `prefix`, ignoring
+    /// the case of ASCII letters.
+    pub(crate) fn with_prefix<'a>(
+        &'a self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        let prefix = prefix.as_bytes();
+
+        let start = self.words.partition_point(|range| {
+            self::compare(self.word(range), prefix) == Ordering::Less
+        });
+
+        self.words[start..]
+            .iter()
+            .map(|range| self.word(range))
+            .take_while(move |word| {
+                word.len() >= prefix.len()
+                    && word[..prefix.len()].eq_ignore_ascii_case(prefix)
+            })
+            .filter_map(|word| std::str::from_utf8(word).ok())
+    }
+}
+
+fn compare(a: &[u8], b: &[u8]) -> Ordering {
+    let a = a.iter().map(u8::to_ascii_lowercase);
+    let b = b.iter().map(u8::to_ascii_lowercase);
+    a.cmp(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_lookup() {
+        let list = WordList::from_words([
+            "foot", "Bar", "food", "fo", "baz", "foo", "football", "foo",
+        ]);
+
+        let words = list.with_prefix("foo").collect::<Vec<_>>();
+        assert_eq!(vec!["foo", "food", "foot", "football"], words);
+
+        let words = list.with_prefix("BA").collect::<Vec<_>>();
+        assert_eq!(vec!["Bar", "baz"], words);
+
+        assert_eq!(None, list.with_prefix("qux").next());
+    }
+}
@@ -1,5 +0,0 @@
-mod lipsum;
-mod words;
-
-pub use lipsum::Lipsum;
-use words::WORDS;
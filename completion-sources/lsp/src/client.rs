@@ -0,0 +1,250 @@
+//! Functions used to talk to the language clients attached to a buffer via
+//! Neovim's Lua API.
+//!
+//! Both the requests and the responses go through `vim.json` instead of being
+//! converted to `Object`s. This lets us handle `vim.NIL`s and arbitrary
+//! `data` payloads without special-casing them.
+
+use completion_types::Document;
+use nvim_oxi::{self as nvim, api, Array, Function, Object};
+use serde::de::DeserializeOwned;
+use tokio::sync::oneshot;
+
+use crate::protocol::{self, Response};
+use crate::{Error, Result};
+
+/// Sends a request to all the language clients attached to a buffer,
+/// calling the handler with the JSON-encoded responses.
+///
+/// If `with_text_document` is set the `textDocument` field of the params is
+/// set to the identifier of the buffer.
+const REQUEST_ALL: &str = r#"
+(function(bufnr, method, params, with_text_document, handler)
+  params = vim.json.decode(params)
+
+  if with_text_document then
+    params.textDocument = { uri = vim.uri_from_bufnr(bufnr) }
+  end
+
+  vim.lsp.buf_request_all(bufnr, method, params, function(results)
+    local responses = {}
+
+    for client_id, response in pairs(results) do
+      local client = vim.lsp.get_client_by_id(client_id)
+
+      table.insert(responses, {
+        client = client and client.name or tostring(client_id),
+        client_id = client_id,
+        error = response.error and response.error.message,
+        result = response.result,
+      })
+    end
+
+    handler(#responses == 0 and "[]" or vim.json.encode(responses))
+  end)
+end)(unpack(_A))
+"#;
+
+/// Sends a request to a single language client, calling the handler with the
+/// JSON-encoded response, or with `null` if the client doesn't exist anymore
+/// or doesn't support the method.
+const REQUEST: &str = r#"
+(function(bufnr, client_id, method, params, handler)
+  local client = vim.lsp.get_client_by_id(client_id)
+
+  if not client or not client.supports_method(method) then
+    return handler("null")
+  end
+
+  client.request(method, vim.json.decode(params), function(err, result)
+    handler(vim.json.encode({
+      client = client.name,
+      client_id = client_id,
+      error = err and err.message,
+      result = result,
+    }))
+  end, bufnr)
+end)(unpack(_A))
+"#;
+
+/// Returns the JSON-encoded union of the trigger characters of all the
+/// language clients attached to a buffer.
+const TRIGGER_CHARACTERS: &str = r#"
+(function(bufnr)
+  local chars = {}
+
+  for _, client in pairs(vim.lsp.get_active_clients({ bufnr = bufnr })) do
+    local provider = client.server_capabilities.completionProvider
+
+    if type(provider) == "table" and type(provider.triggerCharacters) == "table" then
+      for _, char in ipairs(provider.triggerCharacters) do
+        table.insert(chars, char)
+      end
+    end
+  end
+
+  return #chars == 0 and "[]" or vim.json.encode(chars)
+end)(_A)
+"#;
+
+/// Sends a request to all the language clients attached to the document's
+/// buffer, returning the responses of the clients that support the method.
+pub(crate) async fn request_all<T>(
+    document: &Document,
+    method: &'static str,
+    params: serde_json::Value,
+    with_text_document: bool,
+) -> Result<Vec<Response<T>>>
+where
+    T: DeserializeOwned,
+{
+    let params = serde_json::to_string(&params)?;
+    let buffer = document.buffer();
+
+    let json =
+        self::call_with_handler(document, REQUEST_ALL, move |handler| {
+            Array::from_iter([
+                Object::from(buffer),
+                Object::from(method),
+                Object::from(params),
+                Object::from(with_text_document),
+                Object::from(handler),
+            ])
+        })
+        .await?;
+
+    // If the handler was never called no client has responded.
+    let json = match json {
+        Some(json) => json,
+        None => return Ok(Vec::new()),
+    };
+
+    serde_json::from_str::<Vec<Response<T>>>(&json)
+        .map_err(|why| Error::BadResponse { method, why })
+}
+
+/// Sends a request to the language client with the given id, returning its
+/// response or `None` if the client is not running anymore or if it doesn't
+/// support the method.
+pub(crate) async fn request<T>(
+    document: &Document,
+    client_id: u32,
+    method: &'static str,
+    params: serde_json::Value,
+) -> Result<Option<Response<T>>>
+where
+    T: DeserializeOwned,
+{
+    let params = serde_json::to_string(&params)?;
+    let buffer = document.buffer();
+
+    let json = self::call_with_handler(document, REQUEST, move |handler| {
+        Array::from_iter([
+            Object::from(buffer),
+            Object::from(client_id),
+            Object::from(method),
+            Object::from(params),
+            Object::from(handler),
+        ])
+    })
+    .await?;
+
+    let json = match json {
+        Some(json) => json,
+        None => return Ok(None),
+    };
+
+    serde_json::from_str::<Option<Response<T>>>(&json)
+        .map_err(|why| Error::BadResponse { method, why })
+}
+
+/// Returns the union of the trigger characters of all the language clients
+/// attached to the document's buffer.
+pub(crate) async fn trigger_characters(
+    document: &Document,
+) -> Result<Vec<char>> {
+    let buffer = document.buffer();
+
+    let json = document
+        .client_sender
+        .on_nvim_thread(move || {
+            let arg = Object::from(buffer);
+            api::call_function::<_, String>(
+                "luaeval",
+                (TRIGGER_CHARACTERS, arg),
+            )
+        })
+        .await?;
+
+    let mut chars = protocol::seq::<_, String>(
+        &mut serde_json::Deserializer::from_str(&json),
+    )?
+    .into_iter()
+    .filter_map(|s| s.chars().next())
+    .collect::<Vec<_>>();
+
+    chars.sort_unstable();
+    chars.dedup();
+
+    Ok(chars)
+}
+
+/// Returns the lines at the given rows of the document's buffer, skipping the
+/// rows past the end of the buffer.
+pub(crate) async fn get_lines(
+    document: &Document,
+    rows: Vec<u32>,
+) -> Result<Vec<(u32, String)>> {
+    let buffer = document.buffer();
+
+    document
+        .client_sender
+        .on_nvim_thread(move || {
+            let mut lines = Vec::with_capacity(rows.len());
+
+            for row in rows {
+                let line = buffer
+                    .get_lines(row as usize, row as usize + 1, false)?
+                    .next();
+
+                if let Some(line) = line {
+                    lines.push((row, line.to_string_lossy().into_owned()));
+                }
+            }
+
+            Ok::<_, nvim::Error>(lines)
+        })
+        .await
+        .map_err(Into::into)
+}
+
+/// Evaluates a Lua chunk on the Neovim thread, passing it the arguments
+/// returned by `args` as `_A`. The last argument is expected to be a handler
+/// that the chunk calls with its JSON-encoded result.
+///
+/// Returns `None` if the handler was dropped without being called.
+async fn call_with_handler<A>(
+    document: &Document,
+    chunk: &'static str,
+    args: A,
+) -> Result<Option<String>>
+where
+    A: FnOnce(Function<String, ()>) -> Array + Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+
+    document
+        .client_sender
+        .on_nvim_thread(move || {
+            let handler = Function::from_fn_once(move |json: String| {
+                let _ = sender.send(json);
+                Ok::<_, nvim::Error>(())
+            });
+
+            api::call_function::<_, Object>("luaeval", (chunk, args(handler)))
+                .map(|_| ())
+        })
+        .await?;
+
+    Ok(receiver.await.ok())
+}
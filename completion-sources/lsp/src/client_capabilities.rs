@@ -1,7 +1,130 @@
-use nvim_oxi::{self as nvim, Dictionary, Object};
+use nvim_oxi::{
+    self as nvim,
+    serde::{Deserializer, Serializer},
+    Object,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-pub(super) fn client_capabilities(
-    _settings: Object,
-) -> nvim::Result<Dictionary> {
-    Ok(Dictionary::from_iter([("textDocument", Object::nil())]))
+/// Returns the client capabilities passed to the language servers, i.e. the
+/// settings given by the user extended with the `textDocument.completion`
+/// capabilities supported by the plugin.
+///
+/// This is meant to be used together with `vim.lsp.start()` or
+/// `lspconfig`:
+///
+/// ```lua
+/// local lsp = require("nvim-completion").lsp
+/// local capabilities = lsp.client_capabilities(
+///   vim.lsp.protocol.make_client_capabilities()
+/// )
+/// ```
+pub(super) fn client_capabilities(settings: Object) -> nvim::Result<Object> {
+    let mut settings = Value::deserialize(Deserializer::new(settings))?;
+    self::merge(&mut settings, self::completion_capabilities());
+    settings.serialize(Serializer::new()).map_err(Into::into)
+}
+
+/// The `textDocument.completion` capabilities, advertising exactly what the
+/// plugin is able to render and apply.
+fn completion_capabilities() -> Value {
+    json!({
+        "textDocument": {
+            "completion": {
+                "dynamicRegistration": false,
+                "contextSupport": true,
+                "completionItem": {
+                    "snippetSupport": true,
+                    "commitCharactersSupport": false,
+                    "documentationFormat": ["markdown", "plaintext"],
+                    "deprecatedSupport": true,
+                    "preselectSupport": true,
+                    "tagSupport": {
+                        // Only `Deprecated` is defined by the spec.
+                        "valueSet": [1],
+                    },
+                    "insertReplaceSupport": true,
+                    "resolveSupport": {
+                        "properties": ["detail", "documentation"],
+                    },
+                    "labelDetailsSupport": true,
+                },
+                "completionItemKind": {
+                    "valueSet": (1..=25).collect::<Vec<u8>>(),
+                },
+            },
+        },
+    })
+}
+
+/// Recursively merges `other` into `value`, with the values in `other`
+/// taking precedence over the ones in `value`.
+///
+/// If `value` is not an object (e.g. if the user didn't pass any settings,
+/// in which case it's `null`) it's replaced by `other`.
+fn merge(value: &mut Value, other: Value) {
+    let map = match value {
+        Value::Object(map) => map,
+
+        _ => {
+            *value = other;
+            return;
+        },
+    };
+
+    let other = match other {
+        Value::Object(other) => other,
+
+        other => {
+            *value = other;
+            return;
+        },
+    };
+
+    for (key, other) in other {
+        match map.get_mut(&key) {
+            Some(value) => self::merge(value, other),
+            None => {
+                map.insert(key, other);
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_into_null() {
+        let mut settings = Value::Null;
+        merge(&mut settings, completion_capabilities());
+        assert_eq!(settings, completion_capabilities());
+    }
+
+    #[test]
+    fn merge_keeps_other_settings() {
+        let mut settings = json!({
+            "workspace": { "applyEdit": true },
+            "textDocument": {
+                "hover": { "contentFormat": ["plaintext"] },
+                "completion": {
+                    "completionItem": { "snippetSupport": false },
+                },
+            },
+        });
+
+        merge(&mut settings, completion_capabilities());
+
+        assert_eq!(settings["workspace"]["applyEdit"], true);
+
+        assert_eq!(
+            settings["textDocument"]["hover"]["contentFormat"],
+            json!(["plaintext"])
+        );
+
+        let item = &settings["textDocument"]["completion"]["completionItem"];
+        assert_eq!(item["snippetSupport"], true);
+        assert_eq!(item["labelDetailsSupport"], true);
+    }
 }
@@ -0,0 +1,21 @@
+use thiserror::Error as ThisError;
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, ThisError)]
+pub enum Error {
+    #[error("couldn't decode the response of `{method}`: {why}")]
+    BadResponse { method: &'static str, why: serde_json::Error },
+
+    #[error("language server `{client}` returned an error: {message}")]
+    Server { client: String, message: String },
+
+    #[error(transparent)]
+    Nvim(#[from] nvim_oxi::Error),
+
+    #[error(transparent)]
+    NvimApi(#[from] nvim_oxi::api::Error),
+
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+}
@@ -1,4 +1,8 @@
+mod client;
 mod client_capabilities;
+mod error;
 mod lsp;
+mod protocol;
 
+use error::{Error, Result};
 pub use lsp::Lsp;
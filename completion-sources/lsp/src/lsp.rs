@@ -3,14 +3,41 @@ use completion_types::{
     CompletionItem,
     CompletionList,
     CompletionSource,
+    CompletionTrigger,
     Document,
     Position,
+    ResolvedProperties,
 };
 use nvim_oxi::{Dictionary, Function, Object};
 use serde::Deserialize;
-use thiserror::Error as ThisError;
+use serde_json::json;
 
 use super::client_capabilities::client_capabilities;
+use crate::protocol::{
+    self,
+    CompletionResponse,
+    Lines,
+    LspPosition,
+    ResolveData,
+    Response,
+};
+use crate::{client, Error, Result};
+
+/// The method used to request completions.
+const COMPLETION: &str = "textDocument/completion";
+
+/// The method used to resolve a completion item.
+const RESOLVE: &str = "completionItem/resolve";
+
+/// The `Invoked` variant of the LSP's `CompletionTriggerKind`.
+const TRIGGER_KIND_INVOKED: u8 = 1;
+
+/// The `TriggerCharacter` variant of the LSP's `CompletionTriggerKind`.
+const TRIGGER_KIND_TRIGGER_CHARACTER: u8 = 2;
+
+/// The `TriggerForIncompleteCompletions` variant of the LSP's
+/// `CompletionTriggerKind`.
+const TRIGGER_KIND_INCOMPLETE: u8 = 3;
 
 pub struct Lsp;
 
@@ -18,12 +45,6 @@ pub struct Lsp;
 #[serde(deny_unknown_fields)]
 pub struct Config {}
 
-#[derive(Debug, ThisError)]
-#[error("{0}")]
-pub struct Error(&'static str);
-
-type Result<T> = std::result::Result<T, Error>;
-
 #[async_trait]
 impl CompletionSource for Lsp {
     const NAME: &'static str = "lsp";
@@ -46,23 +67,197 @@ impl CompletionSource for Lsp {
 
     async fn trigger_characters(
         &self,
-        _doc: &Document,
+        doc: &Document,
         _config: &Config,
     ) -> Result<Vec<char>> {
-        Ok(Vec::new())
+        client::trigger_characters(doc).await
     }
 
     async fn complete(
         &self,
-        _doc: &Document,
-        _pos: &Position,
+        doc: &Document,
+        pos: &Position,
+        trigger: CompletionTrigger,
         _config: &Config,
     ) -> Result<CompletionList> {
-        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        let context = match trigger {
+            CompletionTrigger::Invoked | CompletionTrigger::Automatic => {
+                json!({ "triggerKind": TRIGGER_KIND_INVOKED })
+            },
+
+            CompletionTrigger::TriggerCharacter(ch) => json!({
+                "triggerKind": TRIGGER_KIND_TRIGGER_CHARACTER,
+                "triggerCharacter": ch.to_string(),
+            }),
+
+            CompletionTrigger::Incomplete => {
+                json!({ "triggerKind": TRIGGER_KIND_INCOMPLETE })
+            },
+        };
+
+        let params = json!({
+            "position": LspPosition::from(pos),
+            "context": context,
+        });
+
+        let responses = client::request_all::<CompletionResponse>(
+            doc, COMPLETION, params, true,
+        )
+        .await?;
+
+        let (results, is_complete) = self::merge_responses(responses)?;
+
+        let mut lines = Lines::new(pos);
+
+        let missing_rows =
+            lines.missing_rows(results.iter().flat_map(|(_, items)| items));
+
+        if !missing_rows.is_empty() {
+            lines.extend(client::get_lines(doc, missing_rows).await?);
+        }
+
+        let items = results
+            .into_iter()
+            .flat_map(|(client_id, items)| {
+                items.into_iter().map(move |item| (client_id, item))
+            })
+            .map(|(client_id, item)| item.into_item(client_id, &lines))
+            .collect();
+
+        Ok(CompletionList { items, is_complete })
+    }
+
+    async fn resolve_completion(
+        &self,
+        doc: &Document,
+        completion: &CompletionItem,
+        _config: &Config,
+    ) -> Result<Option<ResolvedProperties>> {
+        let ResolveData { client_id, item } =
+            match completion.data::<ResolveData>() {
+                Some(data) => data,
+                None => return Ok(None),
+            };
+
+        let params = serde_json::to_value(item)?;
+
+        let response = client::request::<protocol::CompletionItem>(
+            doc, *client_id, RESOLVE, params,
+        )
+        .await?;
+
+        let original = item.properties();
+
+        let properties = match response {
+            Some(response) => {
+                if let Some(message) = response.error {
+                    // The item still has the properties it was sent with, so
+                    // the error is only reported if there's nothing to show.
+                    if !original.is_empty() {
+                        return Ok(Some(original));
+                    }
+
+                    return Err(Error::Server {
+                        client: response.client,
+                        message,
+                    });
+                }
+
+                // Servers only have to fill in the properties that were
+                // missing, so we fall back to the original ones.
+                match response.result.map(|item| item.properties()) {
+                    Some(resolved) => ResolvedProperties {
+                        detail: resolved.detail.or(original.detail),
+                        documentation: resolved
+                            .documentation
+                            .or(original.documentation),
+                    },
+
+                    None => original,
+                }
+            },
+
+            None => original,
+        };
+
+        Ok((!properties.is_empty()).then_some(properties))
+    }
+}
+
+/// The completion items returned by each language client, together with
+/// the id of the client.
+type ClientItems = Vec<(u32, Vec<protocol::CompletionItem>)>;
+
+/// Merges the completions returned by every language client attached to a
+/// buffer, together with whether they're complete.
+///
+/// A client returning an error doesn't discard the completions of the other
+/// ones, and the error is only returned if every client failed.
+fn merge_responses(
+    responses: Vec<Response<CompletionResponse>>,
+) -> Result<(ClientItems, bool)> {
+    let mut results = Vec::new();
+    let mut is_complete = true;
+    let mut error = None;
+    let mut has_succeeded = false;
+
+    for response in responses {
+        if let Some(message) = response.error {
+            // The completions of the failed client are missing, so they're
+            // asked for again on the next keystroke.
+            is_complete = false;
+            error = Some(Error::Server { client: response.client, message });
+            continue;
+        }
+
+        has_succeeded = true;
+
+        let result = match response.result {
+            Some(result) => result,
+            None => continue,
+        };
+
+        is_complete &= !result.is_incomplete();
+
+        results.push((response.client_id, result.into_items()));
+    }
+
+    match error {
+        Some(error) if !has_succeeded => Err(error),
+        _ => Ok((results, is_complete)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn responses(
+        json: serde_json::Value,
+    ) -> Vec<Response<CompletionResponse>> {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn failing_client_keeps_the_others() {
+        let (results, is_complete) = merge_responses(responses(json!([
+            { "client": "rust-analyzer", "client_id": 1,
+              "result": [{ "label": "foo" }] },
+            { "client": "linter", "client_id": 2, "error": "oops" },
+        ])))
+        .unwrap();
+
+        assert_eq!(1, results.len());
+        assert_eq!(1, results[0].0);
+        assert!(!is_complete);
+    }
 
-        let completions =
-            vec![CompletionItem::builder().text("hey from LSP").build()];
+    #[test]
+    fn every_client_failing() {
+        let result = merge_responses(responses(json!([
+            { "client": "linter", "client_id": 2, "error": "oops" },
+        ])));
 
-        Ok(CompletionList { items: completions, is_complete: true })
+        assert!(matches!(result, Err(Error::Server { .. })));
     }
 }
@@ -0,0 +1,553 @@
+//! A minimal subset of the Language Server Protocol types needed by the
+//! source.
+//!
+//! Responses are relayed to us as JSON by Neovim's Lua client, which can't
+//! distinguish between an empty array and an empty object (they're both `{}`
+//! in Lua). Every sequence is therefore deserialized with [`seq`], which
+//! accepts both.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use completion_types::{
+    self as types,
+    CompletionItemKind,
+    InsertTextFormat,
+    Position,
+    ResolvedProperties,
+};
+use serde::de::{self, Deserializer, IgnoredAny};
+use serde::{Deserialize, Serialize};
+
+/// A response returned by a single language client attached to a buffer.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Response<T> {
+    /// The name of the language client that sent the response.
+    pub(crate) client: String,
+
+    /// The id of the language client that sent the response.
+    pub(crate) client_id: u32,
+
+    /// The error message, if the request failed.
+    pub(crate) error: Option<String>,
+
+    /// The result of the request, if the request succeeded.
+    pub(crate) result: Option<T>,
+}
+
+/// The result of a `textDocument/completion` request.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum CompletionResponse {
+    List(CompletionList),
+    Items(#[serde(deserialize_with = "seq")] Vec<CompletionItem>),
+}
+
+impl CompletionResponse {
+    /// Returns whether further typing should result in recomputing the list.
+    #[inline]
+    pub(crate) fn is_incomplete(&self) -> bool {
+        match self {
+            Self::List(list) => list.is_incomplete,
+            Self::Items(_) => false,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn into_items(self) -> Vec<CompletionItem> {
+        match self {
+            Self::List(list) => list.items,
+            Self::Items(items) => items,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompletionList {
+    #[serde(default)]
+    pub(crate) is_incomplete: bool,
+
+    #[serde(deserialize_with = "seq")]
+    pub(crate) items: Vec<CompletionItem>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CompletionItem {
+    pub(crate) label: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) label_details: Option<LabelDetails>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) kind: Option<u8>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(deserialize_with = "seq")]
+    pub(crate) tags: Vec<u8>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) detail: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) documentation: Option<Documentation>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) deprecated: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) preselect: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) sort_text: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) filter_text: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) insert_text: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) insert_text_format: Option<u8>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) text_edit: Option<CompletionTextEdit>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(deserialize_with = "seq")]
+    pub(crate) additional_text_edits: Vec<TextEdit>,
+
+    /// All the other fields we don't care about. They still have to be sent
+    /// back to the server when resolving the item.
+    #[serde(flatten)]
+    pub(crate) rest: HashMap<String, serde_json::Value>,
+}
+
+impl CompletionItem {
+    /// Converts the item into a [`types::CompletionItem`], attaching itself
+    /// and the id of the client that sent it as the item's data so that it
+    /// can later be resolved by the same client.
+    ///
+    /// The positions of the item's text edits are converted from UTF-16 code
+    /// units to bytes using the given lines.
+    pub(crate) fn into_item(
+        self,
+        client_id: u32,
+        lines: &Lines<'_>,
+    ) -> types::CompletionItem {
+        let mut builder = types::CompletionItem::builder();
+
+        builder.text(&*self.label);
+
+        if let Some(insert_text) = &self.insert_text {
+            builder.insert_text(&**insert_text);
+        }
+
+        if self.insert_text_format == Some(INSERT_TEXT_FORMAT_SNIPPET) {
+            builder.insert_text_format(InsertTextFormat::Snippet);
+        }
+
+        if let Some(edit) = self
+            .text_edit
+            .as_ref()
+            .and_then(|edit| edit.to_completion_edit(lines))
+        {
+            builder.text_edit(edit);
+        }
+
+        for edit in &self.additional_text_edits {
+            builder.additional_text_edit(edit.to_text_edit(lines));
+        }
+
+        if let Some(filter_text) = &self.filter_text {
+            builder.filter_text(&**filter_text);
+        }
+
+        if let Some(kind) = self.kind.and_then(CompletionItemKind::from_lsp) {
+            builder.kind(kind);
+        }
+
+        // The detail in the label details is usually more concise than the
+        // item's detail, e.g. a function signature without the function
+        // name.
+        let label_details = self.label_details.as_ref();
+
+        if let Some(detail) = label_details
+            .and_then(|details| details.detail.as_ref())
+            .or(self.detail.as_ref())
+        {
+            builder.detail(&**detail);
+        }
+
+        if let Some(description) =
+            label_details.and_then(|details| details.description.as_ref())
+        {
+            builder.label_description(&**description);
+        }
+
+        if let Some(sort_text) = &self.sort_text {
+            builder.sort_text(&**sort_text);
+        }
+
+        builder
+            .deprecated(
+                self.deprecated.unwrap_or(false)
+                    || self.tags.contains(&TAG_DEPRECATED),
+            )
+            .preselect(self.preselect.unwrap_or(false));
+
+        builder.data(ResolveData { client_id, item: self }).build()
+    }
+
+    /// Returns the properties of the item that are shown in the details
+    /// window.
+    pub(crate) fn properties(&self) -> ResolvedProperties {
+        ResolvedProperties {
+            detail: self.detail.clone(),
+            documentation: self.documentation.clone().map(Into::into),
+        }
+    }
+}
+
+/// Either a `TextEdit` or an `InsertReplaceEdit`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum CompletionTextEdit {
+    InsertReplace(InsertReplaceEdit),
+    Edit(TextEdit),
+}
+
+impl CompletionTextEdit {
+    /// Converts the edit into a [`types::CompletionTextEdit`], returning
+    /// `None` if it doesn't span a single line on the line the completion
+    /// was requested on, as mandated by the spec.
+    fn to_completion_edit(
+        &self,
+        lines: &Lines<'_>,
+    ) -> Option<types::CompletionTextEdit> {
+        let (text, insert, replace) = match self {
+            Self::InsertReplace(edit) => {
+                (&edit.new_text, &edit.insert, &edit.replace)
+            },
+            Self::Edit(edit) => (&edit.new_text, &edit.range, &edit.range),
+        };
+
+        Some(types::CompletionTextEdit {
+            text: text.clone(),
+            insert: lines.cursor_line_range(insert)?,
+            replace: lines.cursor_line_range(replace)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InsertReplaceEdit {
+    pub(crate) new_text: String,
+    pub(crate) insert: LspRange,
+    pub(crate) replace: LspRange,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TextEdit {
+    pub(crate) range: LspRange,
+    pub(crate) new_text: String,
+}
+
+impl TextEdit {
+    fn to_text_edit(&self, lines: &Lines<'_>) -> types::TextEdit {
+        types::TextEdit {
+            start: lines.to_point(self.range.start),
+            end: lines.to_point(self.range.end),
+            text: self.new_text.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub(crate) struct LspRange {
+    pub(crate) start: LspPosition,
+    pub(crate) end: LspPosition,
+}
+
+/// The lines of a buffer needed to convert LSP positions to byte offsets.
+pub(crate) struct Lines<'a> {
+    /// The row of the line the completion was requested on.
+    row: u32,
+
+    /// The line the completion was requested on.
+    line: &'a str,
+
+    /// Any other line referenced by the additional text edits.
+    others: HashMap<u32, String>,
+}
+
+impl<'a> Lines<'a> {
+    #[inline]
+    pub(crate) fn new(position: &'a Position) -> Self {
+        Self {
+            row: position.row,
+            line: &position.line,
+            others: HashMap::new(),
+        }
+    }
+
+    /// Returns the rows of the lines referenced by the additional text edits
+    /// of the items that are not the line the completion was requested on.
+    pub(crate) fn missing_rows<'i, I>(&self, items: I) -> Vec<u32>
+    where
+        I: IntoIterator<Item = &'i CompletionItem>,
+    {
+        let mut rows = items
+            .into_iter()
+            .flat_map(|item| &item.additional_text_edits)
+            .flat_map(|edit| [edit.range.start.line, edit.range.end.line])
+            .filter(|&row| row != self.row)
+            .collect::<Vec<_>>();
+
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+
+    #[inline]
+    pub(crate) fn extend<I: IntoIterator<Item = (u32, String)>>(
+        &mut self,
+        lines: I,
+    ) {
+        self.others.extend(lines)
+    }
+
+    /// Converts the range to a byte range into the line the completion was
+    /// requested on, or returns `None` if the range is on a different line.
+    fn cursor_line_range(&self, range: &LspRange) -> Option<Range<usize>> {
+        (range.start.line == self.row && range.end.line == self.row).then(
+            || {
+                let start =
+                    self::byte_offset(self.line, range.start.character);
+                let end = self::byte_offset(self.line, range.end.character);
+                start..end
+            },
+        )
+    }
+
+    fn to_point(&self, pos: LspPosition) -> (usize, usize) {
+        let line = if pos.line == self.row {
+            Some(self.line)
+        } else {
+            self.others.get(&pos.line).map(|line| &**line)
+        };
+
+        let col = match line {
+            Some(line) => self::byte_offset(line, pos.character),
+
+            // The line is past the end of the buffer.
+            None => 0,
+        };
+
+        (pos.line as usize, col)
+    }
+}
+
+/// Converts an offset in UTF-16 code units into `line` to a byte offset,
+/// clamping it to the length of the line.
+fn byte_offset(line: &str, utf16_offset: u32) -> usize {
+    let mut units = 0;
+
+    for (idx, char) in line.char_indices() {
+        if units >= utf16_offset as usize {
+            return idx;
+        }
+        units += char.len_utf16();
+    }
+
+    line.len()
+}
+
+/// The `Snippet` variant of the LSP's `InsertTextFormat`.
+const INSERT_TEXT_FORMAT_SNIPPET: u8 = 2;
+
+/// The `Deprecated` variant of the LSP's `CompletionItemTag`.
+const TAG_DEPRECATED: u8 = 1;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub(crate) struct LabelDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) detail: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) description: Option<String>,
+}
+
+/// The data attached to every completion item sent by the source.
+pub(crate) struct ResolveData {
+    pub(crate) client_id: u32,
+    pub(crate) item: CompletionItem,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum Documentation {
+    String(String),
+    MarkupContent(MarkupContent),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub(crate) struct MarkupContent {
+    pub(crate) kind: MarkupKind,
+    pub(crate) value: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum MarkupKind {
+    PlainText,
+    Markdown,
+}
+
+impl From<Documentation> for types::Documentation {
+    fn from(doc: Documentation) -> Self {
+        match doc {
+            Documentation::String(text) => Self::PlainText(text),
+
+            Documentation::MarkupContent(MarkupContent { kind, value }) => {
+                match kind {
+                    MarkupKind::PlainText => Self::PlainText(value),
+                    MarkupKind::Markdown => Self::Markdown(value),
+                }
+            },
+        }
+    }
+}
+
+/// A position in a text document expressed as a zero-based line and a
+/// zero-based UTF-16 code unit offset.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub(crate) struct LspPosition {
+    pub(crate) line: u32,
+    pub(crate) character: u32,
+}
+
+impl From<&Position> for LspPosition {
+    #[inline]
+    fn from(pos: &Position) -> Self {
+        let character = self::utf16_len(&pos.line[..pos.col]);
+        Self { line: pos.row, character: character as _ }
+    }
+}
+
+/// Returns the number of UTF-16 code units needed to encode `text`.
+#[inline]
+pub(crate) fn utf16_len(text: &str) -> usize {
+    text.chars().map(char::len_utf16).sum()
+}
+
+/// Deserializes a sequence, treating an empty map as an empty sequence.
+pub(crate) fn seq<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SeqOrMap<T> {
+        Seq(Vec<T>),
+        Map(HashMap<String, IgnoredAny>),
+    }
+
+    match SeqOrMap::deserialize(deserializer)? {
+        SeqOrMap::Seq(seq) => Ok(seq),
+        SeqOrMap::Map(map) if map.is_empty() => Ok(Vec::new()),
+        SeqOrMap::Map(_) => {
+            Err(de::Error::invalid_type(de::Unexpected::Map, &"a sequence"))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_position() {
+        let pos = Position::new(3, 5, "ñañ = 1");
+        let lsp = LspPosition::from(&pos);
+        assert_eq!(3, lsp.line);
+        assert_eq!(3, lsp.character);
+    }
+
+    #[test]
+    fn empty_object_as_items() {
+        let res = r#"{ "isIncomplete": true, "items": {} }"#;
+        let res = serde_json::from_str::<CompletionResponse>(res).unwrap();
+        assert!(res.is_incomplete());
+        assert!(res.into_items().is_empty());
+    }
+
+    #[test]
+    fn markup_documentation() {
+        let item = r##"{
+            "label": "foo",
+            "documentation": { "kind": "markdown", "value": "# foo" }
+        }"##;
+        let item = serde_json::from_str::<CompletionItem>(item).unwrap();
+        let props = item.properties();
+        assert!(props.detail.is_none());
+        assert!(matches!(
+            props.documentation,
+            Some(types::Documentation::Markdown(doc)) if doc == "# foo"
+        ));
+    }
+
+    #[test]
+    fn deprecated_tag() {
+        let item = r#"{ "label": "foo", "kind": 3, "tags": [1] }"#;
+        let item = serde_json::from_str::<CompletionItem>(item).unwrap();
+        let pos = Position::new(0, 0, "");
+        let item = item.into_item(0, &Lines::new(&pos));
+        assert!(item.is_deprecated);
+        assert_eq!(Some(CompletionItemKind::Function), item.kind);
+    }
+
+    #[test]
+    fn utf16_byte_offset() {
+        assert_eq!(0, byte_offset("ñañ", 0));
+        assert_eq!(2, byte_offset("ñañ", 1));
+        assert_eq!(5, byte_offset("ñañ", 3));
+        assert_eq!(5, byte_offset("ñañ", 10));
+    }
+
+    #[test]
+    fn insert_replace_edit() {
+        let item = r#"{
+            "label": "foobar",
+            "textEdit": {
+                "newText": "foobar",
+                "insert": {
+                    "start": { "line": 0, "character": 4 },
+                    "end": { "line": 0, "character": 6 }
+                },
+                "replace": {
+                    "start": { "line": 0, "character": 4 },
+                    "end": { "line": 0, "character": 7 }
+                }
+            }
+        }"#;
+        let item = serde_json::from_str::<CompletionItem>(item).unwrap();
+        let pos = Position::new(0, 7, "ñañ.foo");
+        let item = item.into_item(0, &Lines::new(&pos));
+        let edit = item.text_edit.unwrap();
+        assert_eq!(6..8, edit.insert);
+        assert_eq!(6..9, edit.replace);
+    }
+
+    #[test]
+    fn bare_items() {
+        let res = r#"[{ "label": "foo" }, { "label": "bar", "kind": 3 }]"#;
+        let res = serde_json::from_str::<CompletionResponse>(res).unwrap();
+        assert!(!res.is_incomplete());
+        assert_eq!(2, res.into_items().len());
+    }
+}
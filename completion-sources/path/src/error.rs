@@ -0,0 +1,12 @@
+use thiserror::Error as ThisError;
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, ThisError)]
+pub enum Error {
+    #[error(transparent)]
+    Nvim(#[from] nvim_oxi::Error),
+
+    #[error(transparent)]
+    NvimApi(#[from] nvim_oxi::api::Error),
+}
@@ -0,0 +1,6 @@
+mod error;
+mod path_prefix;
+mod paths;
+
+use error::{Error, Result};
+pub use paths::Paths;
@@ -0,0 +1,149 @@
+use std::ffi::OsString;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// The characters that can't be part of a path being typed.
+const BOUNDARIES: &[char] =
+    &['"', '\'', '`', '(', ')', '[', ']', '{', '}', '<', '>', ',', ';', '='];
+
+/// A path being typed before the cursor, split at its last `/`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct PathPrefix<'a> {
+    /// The directory whose entries are being completed, as typed by the user
+    /// and up to and including the last `/`.
+    pub(crate) dir: &'a str,
+
+    /// The byte range in the line of the file name being typed, i.e. the
+    /// text after the last `/`. Extends past the cursor up to the end of the
+    /// name.
+    pub(crate) name: Range<usize>,
+}
+
+impl<'a> PathPrefix<'a> {
+    /// Looks for a path starting with `./`, `../`, `~/`, `/` or an
+    /// environment variable followed by a `/` (e.g. `$HOME/`) that ends at
+    /// `col`.
+    pub(crate) fn find(line: &'a str, col: usize) -> Option<Self> {
+        let start = line[..col]
+            .rfind(self::is_boundary)
+            .map(|idx| idx + line[idx..].chars().next().unwrap().len_utf8())
+            .unwrap_or(0);
+
+        let slash = start + line[start..col].rfind('/')?;
+
+        let dir = &line[start..=slash];
+
+        if !self::is_path_start(dir) {
+            return None;
+        }
+
+        let end = line[col..]
+            .find(|ch| ch == '/' || self::is_boundary(ch))
+            .map(|idx| col + idx)
+            .unwrap_or(line.len());
+
+        Some(Self { dir, name: slash + 1..end })
+    }
+
+    /// Returns the directory to list, expanding `~` and environment variables
+    /// and resolving relative paths against `relative_to`. Returns `None` if
+    /// an environment variable is not set.
+    pub(crate) fn resolve(&self, relative_to: &Path) -> Option<PathBuf> {
+        self.resolve_with(relative_to, |var| std::env::var_os(var))
+    }
+
+    fn resolve_with<F>(&self, relative_to: &Path, env: F) -> Option<PathBuf>
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        if let Some(rest) = self.dir.strip_prefix("~/") {
+            return env("HOME").map(|home| Path::new(&home).join(rest));
+        }
+
+        if let Some(rest) = self.dir.strip_prefix('$') {
+            let (var, rest) = rest.split_once('/')?;
+            return env(var).map(|value| Path::new(&value).join(rest));
+        }
+
+        Some(relative_to.join(self.dir))
+    }
+}
+
+fn is_boundary(ch: char) -> bool {
+    ch.is_whitespace() || BOUNDARIES.contains(&ch)
+}
+
+fn is_path_start(dir: &str) -> bool {
+    if dir.starts_with("./") || dir.starts_with("../") || dir.starts_with("~/")
+    {
+        return true;
+    }
+
+    // Skip `//`, which is more likely to be a comment than a path.
+    if let Some(rest) = dir.strip_prefix('/') {
+        return !rest.starts_with('/');
+    }
+
+    match dir.strip_prefix('$').and_then(|rest| rest.split_once('/')) {
+        Some((var, _)) => {
+            !var.is_empty()
+                && var
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        },
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(line: &str, expected: Option<(&str, &str)>) {
+        let col = line.find('|').unwrap();
+        let line = line.replacen('|', "", 1);
+
+        let found = PathPrefix::find(&line, col)
+            .map(|prefix| (prefix.dir, &line[prefix.name]));
+
+        assert_eq!(expected, found);
+    }
+
+    #[test]
+    fn find_prefix() {
+        check("./|", Some(("./", "")));
+        check("foo(\"../src/ma|\")", Some(("../src/", "ma")));
+        check("cd ~/fo|o", Some(("~/", "foo")));
+        check("  /usr/bin|/ls", Some(("/usr/", "bin")));
+        check("$HOME/.config/|", Some(("$HOME/.config/", "")));
+        check("foo/bar|", None);
+        check("// comment|", None);
+        check("https://example.com/|", None);
+        check("$/|", None);
+        check("./foo bar|", None);
+    }
+
+    #[test]
+    fn resolve() {
+        let env =
+            |var: &str| (var == "HOME").then(|| OsString::from("/home/me"));
+
+        let resolve = |dir| {
+            PathPrefix { dir, name: 0..0 }
+                .resolve_with(Path::new("/project/src"), env)
+        };
+
+        assert_eq!(Some(PathBuf::from("/project/src/./")), resolve("./"));
+        assert_eq!(
+            Some(PathBuf::from("/project/src/../lib/")),
+            resolve("../lib/")
+        );
+        assert_eq!(
+            Some(PathBuf::from("/home/me/.config/")),
+            resolve("~/.config/")
+        );
+        assert_eq!(Some(PathBuf::from("/home/me/")), resolve("$HOME/"));
+        assert_eq!(Some(PathBuf::from("/etc/")), resolve("/etc/"));
+        assert_eq!(None, resolve("$NOPE/"));
+    }
+}
@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use completion_types::{
+    CompletionItem,
+    CompletionItemKind,
+    CompletionList,
+    CompletionSource,
+    CompletionTextEdit,
+    CompletionTrigger,
+    Document,
+    Position,
+};
+use nvim_oxi::api;
+use serde::Deserialize;
+use tokio::fs;
+
+use crate::path_prefix::PathPrefix;
+use crate::Result;
+
+/// Completes the paths of the files and directories on the filesystem.
+pub struct Paths;
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// What relative paths are resolved against.
+    #[serde(default)]
+    relative_to: RelativeTo,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RelativeTo {
+    /// The directory containing the file being edited, or the current
+    /// working directory if the buffer doesn't have a name.
+    #[default]
+    Document,
+
+    /// Neovim's current working directory.
+    Cwd,
+}
+
+/// An entry of the directory being completed.
+struct Entry {
+    name: String,
+    is_dir: bool,
+}
+
+#[async_trait]
+impl CompletionSource for Paths {
+    const NAME: &'static str = "path";
+
+    type Config = Config;
+
+    type Error = crate::Error;
+
+    async fn enable(&self, _doc: &Document, _config: &Config) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn trigger_characters(
+        &self,
+        _doc: &Document,
+        _config: &Config,
+    ) -> Result<Vec<char>> {
+        Ok(vec!['/'])
+    }
+
+    async fn complete(
+        &self,
+        doc: &Document,
+        pos: &Position,
+        _trigger: CompletionTrigger,
+        config: &Config,
+    ) -> Result<CompletionList> {
+        let items = self::complete(doc, pos, config).await?;
+        Ok(CompletionList { items, is_complete: true })
+    }
+}
+
+async fn complete(
+    doc: &Document,
+    pos: &Position,
+    config: &Config,
+) -> Result<Vec<CompletionItem>> {
+    let prefix = match PathPrefix::find(&pos.line, pos.col) {
+        Some(prefix) => prefix,
+        None => return Ok(Vec::new()),
+    };
+
+    let relative_to = match (&config.relative_to, doc.path().parent()) {
+        (RelativeTo::Document, Some(parent)) if doc.path().is_absolute() => {
+            parent.to_owned()
+        },
+        _ => self::cwd(doc).await?,
+    };
+
+    let dir = match prefix.resolve(&relative_to) {
+        Some(dir) => dir,
+        None => return Ok(Vec::new()),
+    };
+
+    // Hidden files are only shown if the user started typing their name.
+    let show_hidden = pos.line[prefix.name.start..pos.col].starts_with('.');
+
+    // The text matched against the completions is the one returned by
+    // `Position::matched_prefix`, which can start before the file name
+    // (e.g. at the `/` of `./`) or after it (e.g. after the `.` of
+    // `foo.rs`).
+    let matched_start = pos.col - pos.len_prefix();
+
+    let items = self::list(&dir)
+        .await
+        .into_iter()
+        .filter(|entry| show_hidden || !entry.name.starts_with('.'))
+        .filter_map(|entry| {
+            let filter_text = self::filter_text(
+                &pos.line,
+                matched_start,
+                prefix.name.start,
+                &entry.name,
+            )?;
+
+            let kind = match entry.is_dir {
+                true => CompletionItemKind::Folder,
+                false => CompletionItemKind::File,
+            };
+
+            let edit = CompletionTextEdit {
+                text: entry.name.clone(),
+                insert: prefix.name.start..pos.col,
+                replace: prefix.name.clone(),
+            };
+
+            let item = CompletionItem::builder()
+                .text(entry.name)
+                .filter_text(filter_text)
+                .kind(kind)
+                .text_edit(edit)
+                .build();
+
+            Some(item)
+        })
+        .collect();
+
+    Ok(items)
+}
+
+/// Returns Neovim's current working directory.
+async fn cwd(doc: &Document) -> Result<PathBuf> {
+    doc.client_sender
+        .on_nvim_thread(|| api::call_function::<_, String>("getcwd", ()))
+        .await
+        .map(PathBuf::from)
+        .map_err(Into::into)
+}
+
+/// Lists the entries of a directory. Returns an empty list if the directory
+/// can't be read, e.g. because the user is still typing its path.
+async fn list(dir: &Path) -> Vec<Entry> {
+    let mut entries = Vec::new();
+
+    let mut read_dir = match fs::read_dir(dir).await {
+        Ok(read_dir) => read_dir,
+        Err(_) => return entries,
+    };
+
+    while let Ok(Some(entry)) = read_dir.next_entry().await {
+        let name = match entry.file_name().into_string() {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+
+        // Follow symlinks to know if they point to a directory.
+        let is_dir = fs::metadata(entry.path())
+            .await
+            .map(|metadata| metadata.is_dir())
+            .unwrap_or(false);
+
+        entries.push(Entry { name, is_dir });
+    }
+
+    entries
+}
+
+/// Returns the text the matched prefix is matched against for an entry whose
+/// name starts at `name_start`, or `None` if the name doesn't start with the
+/// text typed before the matched prefix (e.g. the `foo.` of `foo.r`).
+fn filter_text(
+    line: &str,
+    matched_start: usize,
+    name_start: usize,
+    name: &str,
+) -> Option<String> {
+    match matched_start <= name_start {
+        true => Some(format!("{}{name}", &line[matched_start..name_start])),
+
+        false => {
+            let typed = &line[name_start..matched_start];
+            name.strip_prefix(typed).map(ToOwned::to_owned)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_text_after_typed_name() {
+        assert_eq!(
+            Some("rs".to_owned()),
+            filter_text("./foo.r", 6, 2, "foo.rs")
+        );
+        assert_eq!(None, filter_text("./foo.r", 6, 2, "foobar.rs"));
+        assert_eq!(
+            Some("~/foo".to_owned()),
+            filter_text("~/foo", 0, 2, "foo")
+        );
+    }
+}
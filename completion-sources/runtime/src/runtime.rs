@@ -3,6 +3,7 @@ use completion_types::{
     CompletionItem,
     CompletionList,
     CompletionSource,
+    CompletionTrigger,
     Document,
     Position,
     RuntimeSource,
@@ -47,6 +48,7 @@ impl CompletionSource for Runtime {
         &self,
         _doc: &Document,
         _pos: &Position,
+        _trigger: CompletionTrigger,
         _config: &Config,
     ) -> Result<CompletionList> {
         let completions =
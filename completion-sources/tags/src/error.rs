@@ -0,0 +1,15 @@
+use thiserror::Error as ThisError;
+
+pub(crate) type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, ThisError)]
+pub enum Error {
+    #[error(transparent)]
+    Nvim(#[from] nvim_oxi::Error),
+
+    #[error(transparent)]
+    NvimApi(#[from] nvim_oxi::api::Error),
+
+    #[error("couldn't read tags file: {0}")]
+    Io(#[from] std::io::Error),
+}
@@ -0,0 +1,6 @@
+mod error;
+mod parse;
+mod tags;
+
+use error::{Error, Result};
+pub use tags::Tags;
@@ -0,0 +1,138 @@
+//! Parses the tags files generated by Exuberant and Universal ctags.
+//!
+//! Every line of a tags file has the format
+//! `{name}\t{file}\t{address}[;"\t{fields}]`, where the optional extension
+//! fields are tab separated `key:value` pairs, except for the kind which
+//! can also be given as a lone letter. Lines starting with `!_TAG_` contain
+//! information about the file itself. See `:h tags-file-format`.
+
+use completion_types::CompletionItemKind;
+
+/// A symbol defined in a tags file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Tag {
+    /// The name of the symbol.
+    pub(crate) name: String,
+
+    /// The file the symbol is defined in, relative to the directory of the
+    /// tags file.
+    pub(crate) file: String,
+
+    /// The line the symbol is defined on, if known.
+    pub(crate) line: Option<usize>,
+
+    /// The kind of the symbol, if known.
+    pub(crate) kind: Option<CompletionItemKind>,
+}
+
+/// Parses the contents of a tags file, returning its tags sorted by name.
+/// Malformed lines are skipped.
+pub(crate) fn parse(contents: &str) -> Vec<Tag> {
+    let mut tags =
+        contents.lines().filter_map(self::parse_line).collect::<Vec<_>>();
+    tags.sort_by(|a, b| a.name.cmp(&b.name));
+    tags
+}
+
+fn parse_line(line: &str) -> Option<Tag> {
+    if line.starts_with("!_TAG_") {
+        return None;
+    }
+
+    let mut fields = line.splitn(3, '\t');
+
+    let name = fields.next().filter(|name| !name.is_empty())?;
+    let file = fields.next()?;
+    let rest = fields.next()?;
+
+    // The address can be a search pattern containing tabs, so the extension
+    // fields start after the last `;"`.
+    let (address, extensions) = match rest.rfind(";\"") {
+        Some(idx) => (&rest[..idx], rest[idx + 2..].trim_start_matches('\t')),
+        None => (rest, ""),
+    };
+
+    let mut line = address.parse::<usize>().ok();
+    let mut kind = None;
+
+    for field in extensions.split('\t').filter(|field| !field.is_empty()) {
+        match field.split_once(':') {
+            Some(("kind", value)) => kind = self::kind(value),
+            Some(("line", value)) => line = value.parse().ok().or(line),
+            Some(_) => {},
+            None => kind = self::kind(field),
+        }
+    }
+
+    Some(Tag { name: name.to_owned(), file: file.to_owned(), line, kind })
+}
+
+/// Maps the kind of a tag, either as a single letter or as its full name, to
+/// the kind of a completion item. The letters are the ones used by most
+/// languages, since their meaning depends on the language of the file.
+fn kind(kind: &str) -> Option<CompletionItemKind> {
+    use CompletionItemKind::*;
+
+    let kind = match kind {
+        "c" | "class" => Class,
+        "d" | "macro" | "define" | "constant" => Constant,
+        "e" | "enumerator" => EnumMember,
+        "f" | "function" | "p" | "prototype" | "func" => Function,
+        "g" | "enum" => Enum,
+        "i" | "interface" | "trait" => Interface,
+        "m" | "member" | "field" => Field,
+        "method" => Method,
+        "n" | "namespace" | "module" | "package" => Module,
+        "s" | "struct" => Struct,
+        "t" | "typedef" | "type" => TypeParameter,
+        "u" | "union" => Struct,
+        "v" | "variable" | "l" | "local" => Variable,
+        _ => return None,
+    };
+
+    Some(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_tags_file() {
+        let contents = "\
+!_TAG_FILE_FORMAT\t2\t/extended format/
+!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted/
+main\tsrc/main.c\t/^int main(int argc, char \
+                        **argv)$/;\"\tf\ttyperef:typename:int
+Point\tsrc/point.h\t12;\"\tkind:struct
+MAX\tsrc/point.h\t/^#define MAX\t10$/;\"\td\tline:3
+broken line
+";
+
+        let tags = parse(contents);
+
+        assert_eq!(
+            vec![
+                Tag {
+                    name: "MAX".into(),
+                    file: "src/point.h".into(),
+                    line: Some(3),
+                    kind: Some(CompletionItemKind::Constant),
+                },
+                Tag {
+                    name: "Point".into(),
+                    file: "src/point.h".into(),
+                    line: Some(12),
+                    kind: Some(CompletionItemKind::Struct),
+                },
+                Tag {
+                    name: "main".into(),
+                    file: "src/main.c".into(),
+                    line: None,
+                    kind: Some(CompletionItemKind::Function),
+                },
+            ],
+            tags
+        );
+    }
+}
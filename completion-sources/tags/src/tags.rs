@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use completion_types::{
+    CompletionItem,
+    CompletionList,
+    CompletionSource,
+    CompletionTrigger,
+    Document,
+    Position,
+};
+use nvim_oxi::api;
+use serde::Deserialize;
+use tokio::{fs, task};
+
+use crate::parse::{self, Tag};
+use crate::Result;
+
+/// Completes the symbols listed in the tags files found via the `tags`
+/// option.
+#[derive(Default)]
+pub struct Tags {
+    /// The tags of every tags file read so far, kept until the file is
+    /// modified.
+    cache: Mutex<HashMap<PathBuf, TagsFile>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {}
+
+/// The tags contained in a tags file, sorted by name.
+#[derive(Clone)]
+struct TagsFile {
+    /// The last modification time of the file when it was parsed.
+    mtime: SystemTime,
+
+    tags: Arc<Vec<Tag>>,
+}
+
+impl Tags {
+    fn cache(&self) -> MutexGuard<'_, HashMap<PathBuf, TagsFile>> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the tags contained in the tags file at `path`, parsing it
+    /// again only if it's been modified since the last time it was read.
+    /// Returns `None` if the file doesn't exist.
+    async fn tags_file(&self, path: PathBuf) -> Result<Option<TagsFile>> {
+        let mtime = match fs::metadata(&path).await {
+            Ok(metadata) => metadata.modified()?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(None)
+            },
+            Err(err) => return Err(err.into()),
+        };
+
+        if let Some(file) = self.cache().get(&path) {
+            if file.mtime == mtime {
+                return Ok(Some(file.clone()));
+            }
+        }
+
+        let contents = fs::read(&path).await?;
+
+        // Tags files of big projects can contain millions of lines, so they're
+        // parsed on a thread where blocking is fine.
+        let tags = task::spawn_blocking(move || {
+            parse::parse(&String::from_utf8_lossy(&contents))
+        })
+        .await
+        .unwrap();
+
+        let file = TagsFile { mtime, tags: Arc::new(tags) };
+
+        self.cache().insert(path, file.clone());
+
+        Ok(Some(file))
+    }
+}
+
+#[async_trait]
+impl CompletionSource for Tags {
+    const NAME: &'static str = "tags";
+
+    type Config = Config;
+
+    type Error = crate::Error;
+
+    async fn enable(&self, _doc: &Document, _config: &Config) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn trigger_characters(
+        &self,
+        _doc: &Document,
+        _config: &Config,
+    ) -> Result<Vec<char>> {
+        Ok(Vec::new())
+    }
+
+    async fn complete(
+        &self,
+        doc: &Document,
+        pos: &Position,
+        _trigger: CompletionTrigger,
+        _config: &Config,
+    ) -> Result<CompletionList> {
+        let prefix = pos.matched_prefix();
+
+        // Completing every symbol of a big project would be too slow, so we
+        // wait for the user to type the first character.
+        if prefix.is_empty() {
+            return Ok(CompletionList {
+                items: Vec::new(),
+                is_complete: false,
+            });
+        }
+
+        let paths = self::tags_files(doc).await?;
+
+        let mut files = Vec::new();
+
+        for path in &paths {
+            files.extend(self.tags_file(path.clone()).await?);
+        }
+
+        // Forget the tags files that are not in the `tags` option anymore.
+        self.cache().retain(|path, _| paths.contains(path));
+
+        let items = files
+            .iter()
+            .flat_map(|file| {
+                // The tags are sorted by name, so the ones starting with the
+                // prefix are all next to each other.
+                let tags = &file.tags;
+                let start = tags.partition_point(|tag| *tag.name < *prefix);
+                tags[start..]
+                    .iter()
+                    .take_while(|tag| tag.name.starts_with(prefix))
+            })
+            .map(self::completion_item)
+            .collect();
+
+        Ok(CompletionList { items, is_complete: true })
+    }
+}
+
+/// Returns the absolute paths of the tags files found via the `tags` option
+/// of the document's buffer.
+async fn tags_files(doc: &Document) -> Result<Vec<PathBuf>> {
+    let buffer = doc.buffer();
+
+    // The `tags` option can be local to the buffer, so `tagfiles()` has to
+    // be called with the document's buffer as the current one.
+    let (cwd, files) = doc
+        .client_sender
+        .on_nvim_thread(move || {
+            buffer.call(|()| {
+                let cwd = api::call_function::<_, String>("getcwd", ())?;
+                let files =
+                    api::call_function::<_, Vec<String>>("tagfiles", ())?;
+                Ok((cwd, files))
+            })
+        })
+        .await?;
+
+    let cwd = PathBuf::from(cwd);
+
+    Ok(files.into_iter().map(|file| cwd.join(file)).collect())
+}
+
+fn completion_item(tag: &Tag) -> CompletionItem {
+    let detail = match tag.line {
+        Some(line) => format!("{}:{line}", tag.file),
+        None => tag.file.clone(),
+    };
+
+    let mut builder = CompletionItem::builder();
+
+    builder.text(tag.name.clone()).detail(detail);
+
+    if let Some(kind) = tag.kind {
+        builder.kind(kind);
+    }
+
+    builder.build()
+}
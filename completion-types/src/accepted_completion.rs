@@ -0,0 +1,17 @@
+use std::time::SystemTime;
+
+/// A completion accepted by the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptedCompletion {
+    /// The text of the accepted item.
+    pub text: String,
+
+    /// The name of the source that provided the item.
+    pub source: String,
+
+    /// The filetype of the buffer the item was inserted in.
+    pub filetype: String,
+
+    /// When the item was accepted.
+    pub timestamp: SystemTime,
+}
@@ -0,0 +1,13 @@
+/// An edit to the text of a buffer, expressed as a range of lines replaced by
+/// some new lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BufferEdit {
+    /// The index of the first replaced line.
+    pub start: usize,
+
+    /// The number of lines that were replaced.
+    pub replaced: usize,
+
+    /// The lines that replaced them.
+    pub lines: Vec<String>,
+}
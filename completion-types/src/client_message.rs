@@ -1,8 +1,18 @@
 use std::sync::Arc;
 
+use nvim_oxi::api::Buffer;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
-use crate::{Clock, CompletionItem, Document, Position, Revision, SourceId};
+use crate::{
+    AcceptedCompletion,
+    BufferEdit,
+    Clock,
+    CompletionItem,
+    Document,
+    Position,
+    Revision,
+    SourceId,
+};
 
 pub type ClientReceiver = UnboundedReceiver<ClientMessage>;
 pub type ClientSender = UnboundedSender<ClientMessage>;
@@ -23,8 +33,23 @@ pub enum ClientMessage {
         id: Revision,
     },
 
+    /// Tells the core the user accepted a completion, which is then ranked
+    /// higher in the following requests.
+    CompletionAccepted { completion: AcceptedCompletion },
+
+    /// Tells the core the text of a buffer has been edited.
+    BufferEdited { buffer: Buffer, edit: BufferEdit },
+
     /// TODO: docs
     CancelRequest { revision: Revision },
+
+    /// Stops the given sources, or all of them if `None`, from providing
+    /// completions in a buffer, or in every buffer if `buffer` is `None`.
+    DetachSources { buffer: Option<Buffer>, sources: Option<Vec<SourceId>> },
+
+    /// Re-attaches sources previously detached with
+    /// [`DetachSources`](ClientMessage::DetachSources).
+    ReattachSources { buffer: Option<Buffer>, sources: Option<Vec<SourceId>> },
 }
 
 #[derive(Debug)]
@@ -46,7 +71,7 @@ pub struct CompletionRequest {
 }
 
 // user continues typing -> (if only 1 char after the first TypedChar, els all)
-// user asks -> RecomputeAll
+// user asks -> Invoked
 // after moving cursor in insert mode -> RecomputeAll
 // after entering insert mode and typing -> RecomputeAll
 
@@ -54,4 +79,8 @@ pub struct CompletionRequest {
 pub enum RequestKind {
     TypedCharacter(char),
     RecomputeAll,
+
+    /// Like [`RecomputeAll`](RequestKind::RecomputeAll), but explicitly
+    /// requested by the user.
+    Invoked,
 }
@@ -1,4 +1,6 @@
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use crate::SourceId;
 
 /// A clock used to measure the performance of the various stages of the
 /// completion pipeline.
@@ -16,18 +18,84 @@ pub struct Clock {
     /// The first 2 happen core-side, while the client is responsible for the
     /// last one. 3 deltas => 4 ticks.
     times: [Option<Instant>; 4],
+
+    /// The timings of the sources whose completions are included in the
+    /// results.
+    sources: Vec<SourceTiming>,
+
+    /// The total time spent waiting to lock the core's state.
+    lock_wait: Duration,
+
+    /// When the sorting of the completions started. The second delta above
+    /// also includes the time spent waiting for a thread to sort them on.
+    sort_started: Option<Instant>,
+}
+
+/// The time a completion source took to compute its completions for a
+/// request.
+#[derive(Clone, Copy, Debug)]
+pub struct SourceTiming {
+    pub source: SourceId,
+
+    /// When the source was asked to compute its completions.
+    pub started: Instant,
+
+    /// When the source sent back its completions.
+    pub finished: Instant,
+
+    /// Whether the completions of the source were reused from a previous
+    /// request instead of being recomputed, in which case `started` and
+    /// `finished` coincide.
+    pub from_cache: bool,
+}
+
+impl SourceTiming {
+    #[cfg(feature = "core")]
+    pub fn cached(source: SourceId) -> Self {
+        let now = Instant::now();
+        Self { source, started: now, finished: now, from_cache: true }
+    }
+
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        self.finished - self.started
+    }
 }
 
 impl Clock {
     #[cfg(feature = "client")]
     pub fn start() -> Self {
-        Self { times: [Some(Instant::now()), None, None, None] }
+        Self {
+            times: [Some(Instant::now()), None, None, None],
+            sources: Vec::new(),
+            lock_wait: Duration::ZERO,
+            sort_started: None,
+        }
     }
 
+    /// Marks the completions of the sources included in the results as
+    /// available.
     #[cfg(feature = "core")]
-    pub fn time_source_finished(&mut self) {
+    pub fn time_source_finished<S>(&mut self, sources: S)
+    where
+        S: IntoIterator<Item = SourceTiming>,
+    {
         debug_assert!(self.times[1..].iter().all(Option::is_none));
         self.times[1] = Some(Instant::now());
+        self.sources.extend(sources);
+    }
+
+    /// Adds the time spent waiting to lock the core's state.
+    #[cfg(feature = "core")]
+    pub fn time_lock_wait(&mut self, waited: Duration) {
+        self.lock_wait += waited;
+    }
+
+    #[cfg(feature = "core")]
+    pub fn time_sort_started(&mut self) {
+        debug_assert!(self.times[1].is_some());
+        debug_assert!(self.sort_started.is_none());
+        self.sort_started = Some(Instant::now());
     }
 
     #[cfg(feature = "core")]
@@ -44,6 +112,29 @@ impl Clock {
         self.times[3] = Some(Instant::now());
     }
 
+    /// The timings of the sources whose completions are included in the
+    /// results.
+    #[cfg(feature = "client")]
+    pub fn sources(&self) -> &[SourceTiming] {
+        &self.sources
+    }
+
+    /// The total time spent waiting to lock the core's state.
+    #[cfg(feature = "client")]
+    pub fn lock_wait(&self) -> Duration {
+        self.lock_wait
+    }
+
+    /// The time spent sorting the completions, excluding the time spent
+    /// waiting for a thread to sort them on.
+    #[cfg(feature = "client")]
+    pub fn sort_time(&self) -> Duration {
+        match (self.sort_started, self.times[2]) {
+            (Some(start), Some(end)) => end - start,
+            _ => Duration::ZERO,
+        }
+    }
+
     #[cfg(feature = "client")]
     pub fn report(&self) -> [u64; 3] {
         debug_assert!(self.times[..].iter().all(Option::is_some));
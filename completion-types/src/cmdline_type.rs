@@ -0,0 +1,32 @@
+/// The kinds of command-line that can be completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CmdlineType {
+    /// An Ex command entered after `:`.
+    Command,
+
+    /// A search pattern entered after `/` or `?`.
+    Search,
+}
+
+impl CmdlineType {
+    /// Converts the value returned by `getcmdtype()`, returning `None` for
+    /// the command-lines we don't complete (e.g. `input()` prompts).
+    #[inline]
+    pub fn from_cmdtype(cmdtype: &str) -> Option<Self> {
+        match cmdtype {
+            ":" => Some(Self::Command),
+            "/" | "?" => Some(Self::Search),
+            _ => None,
+        }
+    }
+
+    /// The character typed to enter this kind of command-line, used in place
+    /// of the filetype for the documents completing it.
+    #[inline]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Command => ":",
+            Self::Search => "/",
+        }
+    }
+}
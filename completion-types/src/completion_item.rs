@@ -1,17 +1,62 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{CompletionItemKind, CompletionTextEdit, TextEdit};
+
 #[derive(Debug, Clone)]
 pub struct CompletionItem {
     pub text: String,
 
     /// TODO: docs
     pub filter_text: Option<String>,
-    // /// TODO: docs
-    // pub sort_text: String,
 
-    // /// TODO: docs
-    // pub insert_text: String,
+    /// The kind of the item, e.g. a function or a variable.
+    pub kind: Option<CompletionItemKind>,
+
+    /// A short, single line detail about the item displayed in the
+    /// completion menu next to its text, e.g. the type of a variable.
+    pub detail: Option<String>,
+
+    /// A description of the item displayed right-aligned in the completion
+    /// menu, e.g. the module a function is defined in.
+    pub label_description: Option<String>,
+
+    /// The text used to compare this item with the other items when their
+    /// scores are the same. If not set the `text` is used instead.
+    pub sort_text: Option<String>,
+
+    /// Whether the item is deprecated. Deprecated items are rendered with a
+    /// strikethrough.
+    pub is_deprecated: bool,
+
+    /// Whether the item should be selected before all the others.
+    pub is_preselected: bool,
+
+    /// How many lines away from the cursor the text of the item appears in
+    /// the buffer, set by the sources completing words found in the buffer.
+    pub distance: Option<u32>,
 
-    // /// TODO: docs
-    // pub text_edit: String,
+    /// The text inserted in the buffer when the item is accepted. If not set
+    /// the `text` is used instead. Ignored if `text_edit` is set.
+    pub insert_text: Option<String>,
+
+    /// How to interpret the text inserted in the buffer, i.e. the
+    /// `insert_text` or the text of the `text_edit`.
+    pub insert_text_format: InsertTextFormat,
+
+    /// The edit applied to the buffer when the item is accepted. If not set
+    /// the completion prefix is replaced with the `insert_text`.
+    pub text_edit: Option<CompletionTextEdit>,
+
+    /// Additional edits applied to the buffer together with the main one
+    /// when the item is accepted, e.g. to add an import at the top of the
+    /// file. They can't overlap with each other or with the main edit.
+    pub additional_text_edits: Vec<TextEdit>,
+
+    /// Opaque data attached to the item by the source that created it, handed
+    /// back to the source when the item is resolved.
+    data: Option<ItemData>,
 }
 
 impl CompletionItem {
@@ -22,6 +67,42 @@ impl CompletionItem {
     pub fn filter_text(&self) -> &str {
         self.filter_text.as_ref().unwrap_or(&self.text)
     }
+
+    pub fn sort_text(&self) -> &str {
+        self.sort_text.as_ref().unwrap_or(&self.text)
+    }
+
+    pub fn insert_text(&self) -> &str {
+        self.insert_text.as_ref().unwrap_or(&self.text)
+    }
+
+    /// Returns the data attached to this item by its source, or `None` if no
+    /// data was attached or if it's not of type `T`.
+    #[inline]
+    pub fn data<T: Any>(&self) -> Option<&T> {
+        self.data.as_ref().and_then(|data| data.0.downcast_ref())
+    }
+}
+
+/// How the text inserted by a completion item should be interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertTextFormat {
+    /// The text is inserted as is.
+    PlainText,
+
+    /// The text is a snippet using the syntax defined by the Language Server
+    /// Protocol, e.g. `foo(${1:bar})$0`.
+    Snippet,
+}
+
+/// A type-erased payload attached to a [`CompletionItem`].
+#[derive(Clone)]
+struct ItemData(Arc<dyn Any + Send + Sync>);
+
+impl fmt::Debug for ItemData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ItemData(..)")
+    }
 }
 
 /// TODO: docs
@@ -33,7 +114,22 @@ pub struct CompletionItemBuilder {
 impl CompletionItemBuilder {
     /// TODO: docs
     pub fn new() -> Self {
-        let item = CompletionItem { text: "".to_owned(), filter_text: None };
+        let item = CompletionItem {
+            text: "".to_owned(),
+            filter_text: None,
+            kind: None,
+            detail: None,
+            label_description: None,
+            sort_text: None,
+            is_deprecated: false,
+            is_preselected: false,
+            distance: None,
+            insert_text: None,
+            insert_text_format: InsertTextFormat::PlainText,
+            text_edit: None,
+            additional_text_edits: Vec::new(),
+            data: None,
+        };
 
         Self { item: Some(item) }
     }
@@ -50,6 +146,87 @@ impl CompletionItemBuilder {
         self
     }
 
+    /// Sets the kind of the item.
+    pub fn kind(&mut self, kind: CompletionItemKind) -> &mut Self {
+        self.item.as_mut().unwrap().kind = Some(kind);
+        self
+    }
+
+    /// Sets the detail displayed next to the item's text.
+    pub fn detail<T: Into<String>>(&mut self, detail: T) -> &mut Self {
+        self.item.as_mut().unwrap().detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the description displayed at the right of the item.
+    pub fn label_description<T: Into<String>>(
+        &mut self,
+        description: T,
+    ) -> &mut Self {
+        self.item.as_mut().unwrap().label_description =
+            Some(description.into());
+        self
+    }
+
+    /// Sets the text used to sort the item.
+    pub fn sort_text<T: Into<String>>(&mut self, text: T) -> &mut Self {
+        self.item.as_mut().unwrap().sort_text = Some(text.into());
+        self
+    }
+
+    /// Marks the item as deprecated.
+    pub fn deprecated(&mut self, is_deprecated: bool) -> &mut Self {
+        self.item.as_mut().unwrap().is_deprecated = is_deprecated;
+        self
+    }
+
+    /// Marks the item as preselected.
+    pub fn preselect(&mut self, is_preselected: bool) -> &mut Self {
+        self.item.as_mut().unwrap().is_preselected = is_preselected;
+        self
+    }
+
+    /// Sets how many lines away from the cursor the item's text appears.
+    pub fn distance(&mut self, distance: u32) -> &mut Self {
+        self.item.as_mut().unwrap().distance = Some(distance);
+        self
+    }
+
+    /// Sets the text inserted when the item is accepted.
+    pub fn insert_text<T: Into<String>>(&mut self, text: T) -> &mut Self {
+        self.item.as_mut().unwrap().insert_text = Some(text.into());
+        self
+    }
+
+    /// Sets how to interpret the inserted text.
+    pub fn insert_text_format(
+        &mut self,
+        format: InsertTextFormat,
+    ) -> &mut Self {
+        self.item.as_mut().unwrap().insert_text_format = format;
+        self
+    }
+
+    /// Sets the edit applied when the item is accepted.
+    pub fn text_edit(&mut self, edit: CompletionTextEdit) -> &mut Self {
+        self.item.as_mut().unwrap().text_edit = Some(edit);
+        self
+    }
+
+    /// Adds an edit applied elsewhere in the buffer when the item is
+    /// accepted.
+    pub fn additional_text_edit(&mut self, edit: TextEdit) -> &mut Self {
+        self.item.as_mut().unwrap().additional_text_edits.push(edit);
+        self
+    }
+
+    /// Attaches some data to the item. It can be retrieved with
+    /// [`CompletionItem::data`], usually when resolving the item.
+    pub fn data<T: Any + Send + Sync>(&mut self, data: T) -> &mut Self {
+        self.item.as_mut().unwrap().data = Some(ItemData(Arc::new(data)));
+        self
+    }
+
     /// TODO: docs
     pub fn build(&mut self) -> CompletionItem {
         self.item.take().unwrap()
@@ -0,0 +1,119 @@
+/// The kind of a completion item. The variants are the same ones defined by
+/// the Language Server Protocol, in the same order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CompletionItemKind {
+    Text,
+    Method,
+    Function,
+    Constructor,
+    Field,
+    Variable,
+    Class,
+    Interface,
+    Module,
+    Property,
+    Unit,
+    Value,
+    Enum,
+    Keyword,
+    Snippet,
+    Color,
+    File,
+    Reference,
+    Folder,
+    EnumMember,
+    Constant,
+    Struct,
+    Event,
+    Operator,
+    TypeParameter,
+}
+
+impl CompletionItemKind {
+    const ALL: [Self; 25] = [
+        Self::Text,
+        Self::Method,
+        Self::Function,
+        Self::Constructor,
+        Self::Field,
+        Self::Variable,
+        Self::Class,
+        Self::Interface,
+        Self::Module,
+        Self::Property,
+        Self::Unit,
+        Self::Value,
+        Self::Enum,
+        Self::Keyword,
+        Self::Snippet,
+        Self::Color,
+        Self::File,
+        Self::Reference,
+        Self::Folder,
+        Self::EnumMember,
+        Self::Constant,
+        Self::Struct,
+        Self::Event,
+        Self::Operator,
+        Self::TypeParameter,
+    ];
+
+    /// Returns the kind associated to the numeric value used by the Language
+    /// Server Protocol, or `None` if the value is out of range.
+    #[inline]
+    pub fn from_lsp(value: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(value).checked_sub(1)?).copied()
+    }
+
+    /// Returns the human readable name of the kind displayed in the
+    /// completion menu.
+    #[inline]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Text => "Text",
+            Self::Method => "Method",
+            Self::Function => "Function",
+            Self::Constructor => "Constructor",
+            Self::Field => "Field",
+            Self::Variable => "Variable",
+            Self::Class => "Class",
+            Self::Interface => "Interface",
+            Self::Module => "Module",
+            Self::Property => "Property",
+            Self::Unit => "Unit",
+            Self::Value => "Value",
+            Self::Enum => "Enum",
+            Self::Keyword => "Keyword",
+            Self::Snippet => "Snippet",
+            Self::Color => "Color",
+            Self::File => "File",
+            Self::Reference => "Reference",
+            Self::Folder => "Folder",
+            Self::EnumMember => "EnumMember",
+            Self::Constant => "Constant",
+            Self::Struct => "Struct",
+            Self::Event => "Event",
+            Self::Operator => "Operator",
+            Self::TypeParameter => "TypeParameter",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_lsp() {
+        assert_eq!(None, CompletionItemKind::from_lsp(0));
+        assert_eq!(
+            Some(CompletionItemKind::Text),
+            CompletionItemKind::from_lsp(1)
+        );
+        assert_eq!(
+            Some(CompletionItemKind::TypeParameter),
+            CompletionItemKind::from_lsp(25)
+        );
+        assert_eq!(None, CompletionItemKind::from_lsp(26));
+    }
+}
@@ -4,8 +4,10 @@ use serde::de::DeserializeOwned;
 
 use crate::source_bundle::SourceConfigPtr;
 use crate::{
+    BufferEdit,
     CompletionItem,
     CompletionList,
+    CompletionTrigger,
     Document,
     GenericError,
     Position,
@@ -18,6 +20,11 @@ pub trait CompletionSource: Send + Sync + 'static {
     /// The name of the completion source.
     const NAME: &'static str;
 
+    /// Whether the source completes the command-line instead of buffers.
+    /// Command-line sources are only enabled for the documents representing
+    /// a command-line, and the other sources are never enabled for them.
+    const CMDLINE: bool = false;
+
     /// TODO: docs
     type Config: Sync + DeserializeOwned;
 
@@ -48,6 +55,7 @@ pub trait CompletionSource: Send + Sync + 'static {
         &self,
         document: &Document,
         position: &Position,
+        trigger: CompletionTrigger,
         config: &Self::Config,
     ) -> Result<CompletionList, Self::Error>;
 
@@ -60,6 +68,17 @@ pub trait CompletionSource: Send + Sync + 'static {
     ) -> Result<Option<ResolvedProperties>, Self::Error> {
         Ok(None)
     }
+
+    /// Called every time the text of a document the source is attached to is
+    /// edited, e.g. to keep an index of its contents up to date without
+    /// having to read the whole buffer again.
+    fn on_edit(
+        &self,
+        _document: &Document,
+        _edit: &BufferEdit,
+        _config: &Self::Config,
+    ) {
+    }
 }
 
 /// TODO: docs
@@ -67,6 +86,8 @@ pub trait CompletionSource: Send + Sync + 'static {
 pub trait ObjectSafeCompletionSource: Send + Sync + 'static {
     fn name(&self) -> &'static str;
 
+    fn is_cmdline(&self) -> bool;
+
     fn api(&self) -> Object;
 
     fn deserialize_config(
@@ -93,6 +114,7 @@ pub trait ObjectSafeCompletionSource: Send + Sync + 'static {
         &self,
         document: &Document,
         position: &Position,
+        trigger: CompletionTrigger,
         config: &SourceConfigPtr,
     ) -> Result<CompletionList, GenericError>;
 
@@ -102,6 +124,13 @@ pub trait ObjectSafeCompletionSource: Send + Sync + 'static {
         item: &CompletionItem,
         config: &SourceConfigPtr,
     ) -> Result<Option<ResolvedProperties>, GenericError>;
+
+    fn on_edit(
+        &self,
+        document: &Document,
+        edit: &BufferEdit,
+        config: &SourceConfigPtr,
+    );
 }
 
 #[async_trait]
@@ -114,6 +143,11 @@ where
         S::NAME
     }
 
+    #[inline]
+    fn is_cmdline(&self) -> bool {
+        S::CMDLINE
+    }
+
     #[inline]
     fn api(&self) -> Object {
         S::api()
@@ -165,12 +199,13 @@ where
         &self,
         document: &Document,
         position: &Position,
+        trigger: CompletionTrigger,
         config: &SourceConfigPtr,
     ) -> Result<CompletionList, GenericError> {
         // Safety: see above.
         let config: &S::Config = unsafe { config.cast() };
 
-        S::complete(self, document, position, config)
+        S::complete(self, document, position, trigger, config)
             .await
             .map_err(|err| Box::new(err) as _)
     }
@@ -189,4 +224,17 @@ where
             .await
             .map_err(|err| Box::new(err) as _)
     }
+
+    #[inline]
+    fn on_edit(
+        &self,
+        document: &Document,
+        edit: &BufferEdit,
+        config: &SourceConfigPtr,
+    ) {
+        // Safety: see above.
+        let config: &S::Config = unsafe { config.cast() };
+
+        S::on_edit(self, document, edit, config)
+    }
 }
@@ -0,0 +1,17 @@
+/// What caused a completion source to be asked to recompute its completions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionTrigger {
+    /// The user explicitly asked for completions.
+    Invoked,
+
+    /// The completions were requested automatically, e.g. after moving the
+    /// cursor or pasting some text.
+    Automatic,
+
+    /// One of the trigger characters of the source was typed.
+    TriggerCharacter(char),
+
+    /// The user kept typing after the source returned an incomplete list of
+    /// completions.
+    Incomplete,
+}
@@ -120,4 +120,9 @@ pub enum CoreMessage {
     /// A completion source returned an error while executing its
     /// [`complete`](crate::CompletionSource::complete) implementation.
     SourceCompleteFailed { source: SourceId, error: GenericError },
+
+    /// A completion source returned an error while executing its
+    /// [`resolve_completion`](crate::CompletionSource::resolve_completion)
+    /// implementation.
+    SourceResolveFailed { source: SourceId, error: GenericError },
 }
@@ -3,7 +3,7 @@ use std::path::{Path, PathBuf};
 use nvim::api::Buffer;
 use nvim_oxi as nvim;
 
-use crate::CoreSender;
+use crate::{CmdlineType, CoreSender};
 
 /// TODO: docs
 #[derive(Debug)]
@@ -16,6 +16,15 @@ pub struct Document {
     // #[cfg_attr(any(feature = "ui", feature = "core"), visibility::make(pub))]
     pub path: PathBuf,
 
+    /// The filetype of the buffer when the document was created. The client
+    /// creates a new document for the buffer when its filetype changes.
+    pub filetype: String,
+
+    /// The command-line the document is used to complete, or `None` if it
+    /// represents a regular buffer. The buffer of a command-line document is
+    /// a scratch buffer that's never edited.
+    pub cmdline: Option<CmdlineType>,
+
     /// TODO: docs
     // #[cfg_attr(feature = "ui", visibility::make(pub))]
     pub client_sender: CoreSender,
@@ -28,6 +37,18 @@ impl Document {
         &self.path
     }
 
+    /// Returns the filetype of the document's buffer.
+    #[inline(always)]
+    pub fn filetype(&self) -> &str {
+        &self.filetype
+    }
+
+    /// Returns the command-line the document is used to complete, if any.
+    #[inline(always)]
+    pub fn cmdline(&self) -> Option<CmdlineType> {
+        self.cmdline
+    }
+
     // /// Has to be called on the Neovim thread.
     // #[inline(always)]
     // fn new(buffer: Buffer, ui_sender: UiSender) -> nvim::Result<Self> {
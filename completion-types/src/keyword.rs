@@ -0,0 +1,148 @@
+//! Parses the value of the `iskeyword` option to tell which characters can
+//! be part of a word.
+//!
+//! See `:h isfname` for the format of the option.
+
+/// The set of characters that make up a word in a buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keyword {
+    /// Whether each character in the `0..=255` range is a keyword character.
+    /// Characters above 255 are keyword characters if they're alphanumeric.
+    table: [bool; 256],
+}
+
+impl Default for Keyword {
+    /// The default value of `iskeyword` on Unix systems.
+    fn default() -> Self {
+        Self::parse("@,48-57,_,192-255")
+    }
+}
+
+impl Keyword {
+    /// Parses the value of the `iskeyword` option. Invalid parts of the option
+    /// are ignored.
+    pub fn parse(option: &str) -> Self {
+        let mut table = [false; 256];
+
+        for part in option.split(',').filter(|part| !part.is_empty()) {
+            // A lone `^` is the `^` character itself, not an exclusion.
+            let (part, value) = match part.strip_prefix('^') {
+                Some(rest) if !rest.is_empty() => (rest, false),
+                _ => (part, true),
+            };
+
+            // `@` stands for all the alphabetic characters, while `@-@` is
+            // the `@` character itself.
+            if part == "@" {
+                for (byte, is_keyword) in table.iter_mut().enumerate() {
+                    if (byte as u8 as char).is_alphabetic() {
+                        *is_keyword = value;
+                    }
+                }
+                continue;
+            }
+
+            let (start, end) = match self::parse_range(part) {
+                Some(range) => range,
+                None => continue,
+            };
+
+            if start <= end {
+                table[start as usize..=end as usize].fill(value);
+            }
+        }
+
+        Self { table }
+    }
+
+    /// Whether `ch` can be part of a word.
+    pub fn contains(&self, ch: char) -> bool {
+        match u8::try_from(ch) {
+            Ok(byte) => self.table[byte as usize],
+            Err(_) => ch.is_alphanumeric(),
+        }
+    }
+
+    /// Returns an iterator over the words contained in `line`.
+    pub fn words<'a>(
+        &'a self,
+        line: &'a str,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        line.split(|ch| !self.contains(ch)).filter(|word| !word.is_empty())
+    }
+}
+
+/// Parses a single character or a `start-end` range of characters, where both
+/// ends can be either a character or its decimal code.
+fn parse_range(part: &str) -> Option<(u8, u8)> {
+    let mut chars = part.char_indices();
+
+    // The first character is always part of the start, so that `--45` is the
+    // range between `-` and `-`.
+    chars.next()?;
+
+    match chars.find(|&(_, ch)| ch == '-').map(|(idx, _)| idx) {
+        Some(idx) => {
+            let start = self::parse_char(&part[..idx])?;
+            let end = self::parse_char(&part[idx + 1..])?;
+            Some((start, end))
+        },
+
+        None => self::parse_char(part).map(|ch| (ch, ch)),
+    }
+}
+
+fn parse_char(s: &str) -> Option<u8> {
+    if !s.is_empty() && s.bytes().all(|byte| byte.is_ascii_digit()) {
+        return s.parse().ok();
+    }
+
+    let mut chars = s.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => u8::try_from(ch).ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_iskeyword() {
+        let keyword = Keyword::default();
+
+        assert!(keyword.contains('a'));
+        assert!(keyword.contains('Z'));
+        assert!(keyword.contains('7'));
+        assert!(keyword.contains('_'));
+        assert!(keyword.contains('é'));
+        assert!(keyword.contains('λ'));
+        assert!(!keyword.contains('-'));
+        assert!(!keyword.contains('@'));
+        assert!(!keyword.contains(' '));
+
+        let words =
+            keyword.words("foo_bar(baz, 42) - qux").collect::<Vec<_>>();
+        assert_eq!(vec!["foo_bar", "baz", "42", "qux"], words);
+    }
+
+    #[test]
+    fn ranges_and_exclusions() {
+        let keyword = Keyword::parse("@,48-57,_,-,^a,@-@,^,45");
+
+        assert!(keyword.contains('-'));
+        assert!(keyword.contains('@'));
+        assert!(keyword.contains('^'));
+        assert!(keyword.contains('b'));
+        assert!(!keyword.contains('a'));
+
+        let keyword = Keyword::parse("a-c,^b");
+
+        assert!(keyword.contains('a'));
+        assert!(!keyword.contains('b'));
+        assert!(keyword.contains('c'));
+        assert!(!keyword.contains('d'));
+    }
+}
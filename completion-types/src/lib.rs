@@ -1,17 +1,29 @@
+mod accepted_completion;
+mod buffer_edit;
 mod client_message;
 mod clock;
+mod cmdline_type;
 mod completion_item;
+mod completion_item_kind;
 mod completion_list;
 mod completion_source;
+mod completion_trigger;
 mod core_message;
 mod document;
+mod keyword;
+mod matcher_config;
 mod position;
+mod ranking_weights;
 mod resolved_properties;
 mod revision;
 mod scored_completion;
 mod source_bundle;
 mod source_enable;
+mod source_limits;
+mod text_edit;
 
+pub use accepted_completion::AcceptedCompletion;
+pub use buffer_edit::BufferEdit;
 pub use client_message::{
     ClientMessage,
     ClientReceiver,
@@ -19,19 +31,31 @@ pub use client_message::{
     CompletionRequest,
     RequestKind,
 };
-pub use clock::Clock;
+pub use clock::{Clock, SourceTiming};
+pub use cmdline_type::CmdlineType;
 pub use completion_derive::RuntimeSource;
-pub use completion_item::{CompletionItem, CompletionItemBuilder};
+pub use completion_item::{
+    CompletionItem,
+    CompletionItemBuilder,
+    InsertTextFormat,
+};
+pub use completion_item_kind::CompletionItemKind;
 pub use completion_list::CompletionList;
 pub use completion_source::{CompletionSource, ObjectSafeCompletionSource};
+pub use completion_trigger::CompletionTrigger;
 pub use core_message::{CoreMessage, CoreReceiver, CoreSender};
 pub use document::Document;
+pub use keyword::Keyword;
+pub use matcher_config::{CaseMatching, MatcherConfig, MatcherKind};
 pub use position::Position;
-pub use resolved_properties::ResolvedProperties;
+pub use ranking_weights::RankingWeights;
+pub use resolved_properties::{Documentation, ResolvedProperties};
 pub use revision::Revision;
 pub use scored_completion::ScoredCompletion;
 pub use source_bundle::{SourceBundle, SourceId};
 pub use source_enable::SourceEnable;
+pub use source_limits::SourceLimits;
+pub use text_edit::{CompletionTextEdit, TextEdit};
 
 pub type GenericError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
@@ -0,0 +1,50 @@
+use serde::Deserialize;
+
+/// Configures how the completions are filtered and scored against the prefix
+/// typed by the user.
+#[derive(Debug, Default, Clone)]
+pub struct MatcherConfig {
+    pub kind: MatcherKind,
+    pub case: CaseMatching,
+
+    /// The maximum number of mistyped characters a prefix can contain while
+    /// still matching a completion. A typo is a substitution: the mistyped
+    /// character takes the place of a different one in the completion, so
+    /// missing or extra characters don't count as typos. At least one
+    /// character of the prefix has to be typed correctly.
+    pub max_typos: u8,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatcherKind {
+    /// Fuzzy matching tuned for code completion, the same used by `clangd`.
+    Fuzzy,
+
+    /// Completions have to start with the prefix.
+    Prefix,
+
+    /// Completions have to contain the prefix.
+    Substring,
+
+    /// Fuzzy matching with the scoring algorithm of `fzf`.
+    #[default]
+    FzfV2,
+
+    /// Completions have to be equal to the prefix.
+    Exact,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaseMatching {
+    /// Upper and lower case characters never match each other.
+    Respect,
+
+    /// Upper and lower case characters always match each other.
+    Ignore,
+
+    /// Case is ignored unless the prefix contains an upper case character.
+    #[default]
+    Smart,
+}
@@ -0,0 +1,41 @@
+use serde::Deserialize;
+
+/// The weights of the components mixed together to rank the completions
+/// matching the prefix. Every component is normalized between 0 and 1 before
+/// being weighted, so setting a weight to 0 disables that component.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RankingWeights {
+    /// How well the completion matches the prefix.
+    pub score: f32,
+
+    /// The `priority` of the source that provided the completion.
+    pub source_priority: f32,
+
+    /// The position of the completion among the ones of the same source when
+    /// sorted by their sort text.
+    pub sort_text: f32,
+
+    /// Whether the source asked for the completion to be preselected.
+    pub preselect: f32,
+
+    /// How close to the cursor the text of the completion appears in the
+    /// buffer.
+    pub locality: f32,
+
+    /// How often and how recently the completion has been accepted.
+    pub frecency: f32,
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            score: 1.0,
+            source_priority: 0.5,
+            sort_text: 0.2,
+            preselect: 0.5,
+            locality: 0.2,
+            frecency: 0.5,
+        }
+    }
+}
@@ -1,8 +1,39 @@
-/// TODO: docs
+/// Additional informations about a completion item that are computed lazily
+/// by the source when the item is selected.
+#[derive(Debug, Clone, Default)]
 pub struct ResolvedProperties {
-    /// TODO: docs
+    /// A short, usually single line, description of the item, e.g. the
+    /// signature of a function.
     pub detail: Option<String>,
 
-    /// TODO: docs
-    pub documentation: Option<String>,
+    /// The documentation of the item.
+    pub documentation: Option<Documentation>,
+}
+
+impl ResolvedProperties {
+    /// Returns `true` if there's nothing to display.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.detail.is_none() && self.documentation.is_none()
+    }
+}
+
+/// The documentation of a completion item.
+#[derive(Debug, Clone)]
+pub enum Documentation {
+    /// Text that should be displayed as is.
+    PlainText(String),
+
+    /// Text that should be rendered as Markdown.
+    Markdown(String),
+}
+
+impl Documentation {
+    /// Returns the raw text of the documentation.
+    #[inline]
+    pub fn text(&self) -> &str {
+        match self {
+            Self::PlainText(text) | Self::Markdown(text) => text,
+        }
+    }
 }
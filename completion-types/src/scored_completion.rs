@@ -1,18 +1,30 @@
 use std::ops::RangeInclusive;
 use std::sync::Arc;
 
-use crate::CompletionItem;
+use crate::{CompletionItem, SourceId};
 
 pub type Score = i64;
 
 #[derive(Debug)]
 pub struct ScoredCompletion {
     pub item: Arc<CompletionItem>,
+    pub source: SourceId,
     pub score: Score,
     pub matched_bytes: Vec<usize>,
+
+    /// The items with the same insert text provided by other sources (or by
+    /// the same one), which were merged into this one.
+    pub duplicates: Vec<(SourceId, Arc<CompletionItem>)>,
 }
 
 impl ScoredCompletion {
+    /// Returns the ids of all the sources that provided this completion,
+    /// starting from the one whose item is displayed.
+    pub fn sources(&self) -> impl Iterator<Item = SourceId> + '_ {
+        std::iter::once(self.source)
+            .chain(self.duplicates.iter().map(|(source, _)| *source))
+    }
+
     pub fn matched_ranges(&self) -> Vec<RangeInclusive<usize>> {
         to_ranges(&self.matched_bytes)
     }
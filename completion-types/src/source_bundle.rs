@@ -1,6 +1,11 @@
 use std::sync::Arc;
 
-use crate::{CompletionSource, ObjectSafeCompletionSource, SourceEnable};
+use crate::{
+    CompletionSource,
+    ObjectSafeCompletionSource,
+    SourceEnable,
+    SourceLimits,
+};
 
 pub type SourceId = &'static str;
 
@@ -17,6 +22,13 @@ pub struct SourceBundle {
 
     // #[cfg_attr(feature = "core", visibility::make(pub))]
     pub enable: Option<SourceEnable>,
+
+    /// How the completions of this source are ranked relative to the ones of
+    /// the other sources, the higher the better.
+    pub priority: i32,
+
+    /// Limits the completions of this source shown in each request.
+    pub limits: SourceLimits,
 }
 
 impl<S> From<S> for SourceBundle
@@ -30,6 +42,8 @@ where
             source: Arc::new(source),
             config: None,
             enable: None,
+            priority: 0,
+            limits: SourceLimits::default(),
         }
     }
 }
@@ -42,6 +56,8 @@ impl From<Box<dyn ObjectSafeCompletionSource>> for SourceBundle {
             source: Arc::from(source),
             config: None,
             enable: None,
+            priority: 0,
+            limits: SourceLimits::default(),
         }
     }
 }
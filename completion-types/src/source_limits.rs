@@ -0,0 +1,15 @@
+/// Limits the completions a source contributes to each request.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SourceLimits {
+    /// The maximum number of completions of the source shown at once, or
+    /// `None` to show all of them.
+    pub max_items: Option<usize>,
+
+    /// The minimum number of characters the prefix has to contain before the
+    /// source is asked for completions.
+    pub min_prefix_length: usize,
+
+    /// The minimum number of characters of a completion's text. Shorter
+    /// completions are discarded.
+    pub keyword_length: usize,
+}
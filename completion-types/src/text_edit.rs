@@ -0,0 +1,34 @@
+use std::ops::Range;
+
+/// An edit replacing a range of text in a buffer. Rows are zero-based and
+/// columns are zero-based byte offsets into the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The `(row, col)` where the replaced range starts.
+    pub start: (usize, usize),
+
+    /// The `(row, col)` where the replaced range ends (exclusive).
+    pub end: (usize, usize),
+
+    /// The text to insert in place of the range. Can contain newlines.
+    pub text: String,
+}
+
+/// An edit on the line the completion was requested on, used to insert a
+/// completion item in the buffer in place of the text matched by the source.
+///
+/// Both ranges are byte ranges into the line the completion was requested on
+/// and have to contain the column of the cursor at the time of the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompletionTextEdit {
+    /// The text to insert in place of the range. Can contain newlines.
+    pub text: String,
+
+    /// The range replaced when the completion is inserted. It usually ends
+    /// at the cursor.
+    pub insert: Range<usize>,
+
+    /// The range replaced when the completion replaces the word under the
+    /// cursor. It usually extends past the cursor up to the end of the word.
+    pub replace: Range<usize>,
+}